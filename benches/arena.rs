@@ -0,0 +1,75 @@
+/*
+ * Compares parsing a document of 100,000 small objects with parse, which makes a
+ * separate allocation for every string, object and array, against parse_in_arena,
+ * which allocates from a single arena. Before the timings, the number of calls to
+ * the global allocator made by each is printed, which is counted by wrapping the
+ * system allocator; the arena is reset between iterations, so that once it has
+ * grown to the size of the document it is not allocated again.
+ *
+ * Run with "cargo bench --features bumpalo --bench arena".
+ */
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rs_cargo::Arena;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_arena(c: &mut Criterion) {
+    let element = "{\"id\": 12345, \"name\": \"cargo\", \"tags\": [\"a\", \"b\", null]}";
+    let doc = format!("[{}]", vec![element; 100_000].join(","));
+    let mut arena = Arena::new();
+    let by_parse = allocations(|| drop(rs_cargo::parse(doc.as_bytes()).unwrap()));
+    let by_arena = allocations(|| {
+        rs_cargo::parse_in_arena(doc.as_bytes(), &arena).unwrap();
+    });
+    arena.reset();
+    let reused = allocations(|| {
+        rs_cargo::parse_in_arena(doc.as_bytes(), &arena).unwrap();
+    });
+    println!(
+        "allocations: parse {by_parse}, parse_in_arena {by_arena} ({reused} reusing the arena)"
+    );
+
+    let mut group = c.benchmark_group("small_values");
+    group.throughput(Throughput::Bytes(doc.len() as u64));
+    group.sample_size(10);
+    group.bench_function("parse", |b| {
+        b.iter(|| rs_cargo::parse(doc.as_bytes()).unwrap())
+    });
+    group.bench_function("parse_in_arena", |b| {
+        b.iter(|| {
+            arena.reset();
+            rs_cargo::parse_in_arena(doc.as_bytes(), &arena)
+                .map(|_| ())
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_arena);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_cargo::{from_reader_with_config, parse, validate_all, validate_only, ParseConfig};
+
+/*
+ * Whatever the input, reading it must give either a value or a CargoError and
+ * never panic. The tree parser and the event parser (through validate_only) must
+ * also agree on whether it is valid, and the other options are exercised too.
+ */
+fuzz_target!(|data: &[u8]| {
+    let value = parse(data);
+    assert_eq!(value.is_ok(), validate_only(data).is_ok());
+    let config = ParseConfig::new()
+        .relaxed(true)
+        .reject_duplicate_keys(true)
+        .iterative(true);
+    let _ = from_reader_with_config(data, &config);
+    let _ = validate_all(data);
+    if let Ok(value) = value {
+        let _ = value.to_string();
+    }
+});
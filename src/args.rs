@@ -1,19 +1,320 @@
-use std::error::Error;
+use std::{error::Error, fmt, path::PathBuf};
 
-fn is_num_args_valid(argc: usize) -> bool {
-    match argc {
-        2 | 3 | 4 => true,
-        1 | _ => false,
+/*
+ * The default number of spaces per indentation level when -p is given without
+ * an INDENT operand.
+ */
+const DEFAULT_INDENT: u32 = 4;
+
+/*
+ * The most significant digits that --max-precision may ask for, since an f64
+ * never needs more than 17 to be written exactly.
+ */
+const MAX_PRECISION: u32 = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CargoMode {
+    Help,
+    Validate,
+    Canonicalize,
+}
+
+/*
+ * When to color pretty-printed output, as chosen with --color. With Auto, it is
+ * colored only if it goes to a terminal and NO_COLOR is not set.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/*
+ * The settings selected by the command-line arguments. The "indent" field is
+ * Some only if pretty-printing was requested with -p. The "input" and "output"
+ * fields are Some only if the input is to be read from a file rather than standard
+ * input, or the canonical output written to a file rather than standard output.
+ * The "stats" field is set by --stats, which may be given with either -v or -c,
+ * or on its own, in which case the input is just validated.
+ * The "precision" field is Some only if --max-precision was given with -c, and
+ * "final_newline" is false only if --no-final-newline was.
+ * The "depth" field is Some only if --depth was given, in any mode.
+ * The "select" field is Some only if --select was given, which implies -c if no
+ * mode is. The "color" field is Auto unless --color was given with -c, and
+ * "measure" is set by --measure, which may only be given with -c.
+ * The "report_all" field is set by --report-all, which implies -v if no mode is
+ * given and may not be combined with -c or --stats.
+ * The "in_place" field is set by -i (or --in-place), which may only be given with
+ * -c and -f, and not with -o, as the output replaces the input file, nor with
+ * --measure, which writes no output to replace it with.
+ */
+#[derive(Debug, Clone)]
+pub struct CargoArgs {
+    pub mode: CargoMode,
+    pub indent: Option<u32>,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub stats: bool,
+    pub precision: Option<usize>,
+    pub final_newline: bool,
+    pub depth: Option<usize>,
+    pub select: Option<String>,
+    pub color: ColorChoice,
+    pub measure: bool,
+    pub report_all: bool,
+    pub in_place: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArgError {
+    NoMode,
+    ConflictingModes,
+    UnknownFlag(String),
+    MissingOperand(&'static str),
+    PrettyWithoutCanonicalize,
+    OutputWithoutCanonicalize,
+    PrecisionWithoutCanonicalize,
+    NewlineWithoutCanonicalize,
+    SelectWithoutCanonicalize,
+    ColorWithoutCanonicalize,
+    MeasureWithoutCanonicalize,
+    ReportAllWithoutValidate,
+    InPlaceWithoutCanonicalize,
+    InPlaceWithoutFile,
+    InPlaceWithOutput,
+    InPlaceWithMeasure,
+    InvalidOperand(&'static str),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::NoMode => write!(f, "one of -h, -v, -c or --stats must be given"),
+            ArgError::ConflictingModes => write!(f, "-c and -v cannot be given together"),
+            ArgError::UnknownFlag(flag) => write!(f, "unknown flag '{}'", flag),
+            ArgError::MissingOperand(flag) => write!(f, "{} requires an operand", flag),
+            ArgError::PrettyWithoutCanonicalize => write!(f, "-p may only be given with -c"),
+            ArgError::OutputWithoutCanonicalize => write!(f, "-o may only be given with -c"),
+            ArgError::PrecisionWithoutCanonicalize => {
+                write!(f, "--max-precision may only be given with -c")
+            }
+            ArgError::NewlineWithoutCanonicalize => {
+                write!(f, "--no-final-newline may only be given with -c")
+            }
+            ArgError::SelectWithoutCanonicalize => write!(f, "--select may only be given with -c"),
+            ArgError::ColorWithoutCanonicalize => write!(f, "--color may only be given with -c"),
+            ArgError::MeasureWithoutCanonicalize => {
+                write!(f, "--measure may only be given with -c")
+            }
+            ArgError::ReportAllWithoutValidate => {
+                write!(
+                    f,
+                    "--report-all may only be given with -v, and not with --stats"
+                )
+            }
+            ArgError::InPlaceWithoutCanonicalize => write!(f, "-i may only be given with -c"),
+            ArgError::InPlaceWithoutFile => {
+                write!(f, "-i requires -f, as standard input cannot be replaced")
+            }
+            ArgError::InPlaceWithOutput => write!(f, "-i and -o cannot be given together"),
+            ArgError::InPlaceWithMeasure => {
+                write!(f, "-i and --measure cannot be given together")
+            }
+            ArgError::InvalidOperand(flag) => write!(f, "invalid operand for {}", flag),
+        }
     }
 }
-pub fn are_cargo_args_valid(argc: usize, argv: Vec<String>) -> bool {
-    if !is_num_args_valid(argc) {
-        return false;
-    } else {
-        true
+
+impl Error for ArgError {}
+
+/*
+ * The INDENT operand (like those of --max-precision and --depth) has the same
+ * format as a nonnegative integer in the JSON specification, so leading zeros
+ * are not permitted.
+ */
+fn parse_indent(arg: &str) -> Option<u32> {
+    let mut chars = arg.chars();
+    match chars.next()? {
+        '0' if arg.len() == 1 => Some(0),
+        '1'..='9' if chars.all(|c| c.is_ascii_digit()) => arg.parse().ok(),
+        _ => None,
     }
 }
 
-pub fn cargo_init(argv: Vec<String>) -> Result<(), Box<dyn Error>> {
-    Ok(())
+pub fn parse_args(argv: &[String]) -> Result<CargoArgs, ArgError> {
+    let mut mode = None;
+    let mut indent = None;
+    let mut input = None;
+    let mut output = None;
+    let mut stats = false;
+    let mut precision = None;
+    let mut final_newline = true;
+    let mut depth = None;
+    let mut select = None;
+    let mut color = None;
+    let mut measure = false;
+    let mut report_all = false;
+    let mut in_place = false;
+    let mut tokens = argv.iter().skip(1).peekable();
+    while let Some(token) = tokens.next() {
+        if token == "--stats" {
+            stats = true;
+            continue;
+        }
+        if token == "--no-final-newline" {
+            final_newline = false;
+            continue;
+        }
+        if token == "--measure" {
+            measure = true;
+            continue;
+        }
+        if token == "--report-all" {
+            report_all = true;
+            continue;
+        }
+        if token == "--in-place" {
+            in_place = true;
+            continue;
+        }
+        if token == "--max-precision" {
+            let operand = tokens
+                .next()
+                .ok_or(ArgError::MissingOperand("--max-precision"))?;
+            let n = parse_indent(operand)
+                .filter(|n| (1..=MAX_PRECISION).contains(n))
+                .ok_or(ArgError::InvalidOperand("--max-precision"))?;
+            precision = Some(n as usize);
+            continue;
+        }
+        if token == "--depth" {
+            let operand = tokens.next().ok_or(ArgError::MissingOperand("--depth"))?;
+            let n = parse_indent(operand).ok_or(ArgError::InvalidOperand("--depth"))?;
+            depth = Some(n as usize);
+            continue;
+        }
+        if token == "--select" {
+            let operand = tokens.next().ok_or(ArgError::MissingOperand("--select"))?;
+            select = Some(operand.clone());
+            continue;
+        }
+        if token == "--color" {
+            let operand = tokens.next().ok_or(ArgError::MissingOperand("--color"))?;
+            color = Some(match operand.as_str() {
+                "auto" => ColorChoice::Auto,
+                "always" => ColorChoice::Always,
+                "never" => ColorChoice::Never,
+                _ => return Err(ArgError::InvalidOperand("--color")),
+            });
+            continue;
+        }
+        // Several flags may be combined into one token, as in -cp4 or -cp 4.
+        let flags = match token.strip_prefix('-') {
+            Some(flags) if !flags.is_empty() => flags,
+            _ => return Err(ArgError::UnknownFlag(token.to_string())),
+        };
+        for (i, flag) in flags.char_indices() {
+            let rest = &flags[i + flag.len_utf8()..];
+            let selected = match flag {
+                'h' => CargoMode::Help,
+                'v' => CargoMode::Validate,
+                'c' => CargoMode::Canonicalize,
+                'i' => {
+                    in_place = true;
+                    continue;
+                }
+                'p' => {
+                    indent = Some(DEFAULT_INDENT);
+                    if !rest.is_empty() {
+                        if let Some(n) = parse_indent(rest) {
+                            indent = Some(n);
+                            break;
+                        }
+                    } else if let Some(n) = tokens.peek().and_then(|arg| parse_indent(arg)) {
+                        indent = Some(n);
+                        tokens.next();
+                    }
+                    continue;
+                }
+                'f' | 'o' => {
+                    let operand = match (rest, flag) {
+                        ("", 'f') => tokens.next().ok_or(ArgError::MissingOperand("-f"))?,
+                        ("", _) => tokens.next().ok_or(ArgError::MissingOperand("-o"))?,
+                        (rest, _) => rest,
+                    };
+                    if flag == 'f' {
+                        input = Some(PathBuf::from(operand));
+                    } else {
+                        output = Some(PathBuf::from(operand));
+                    }
+                    break;
+                }
+                _ => return Err(ArgError::UnknownFlag(format!("-{}", flag))),
+            };
+            mode = match (mode, selected) {
+                (Some(CargoMode::Help), _) | (_, CargoMode::Help) => Some(CargoMode::Help),
+                (Some(previous), _) if previous != selected => {
+                    return Err(ArgError::ConflictingModes)
+                }
+                _ => Some(selected),
+            };
+        }
+    }
+    let mode = match mode {
+        None if select.is_some() => CargoMode::Canonicalize,
+        None if stats || report_all => CargoMode::Validate,
+        mode => mode.ok_or(ArgError::NoMode)?,
+    };
+    if indent.is_some() && mode == CargoMode::Validate {
+        return Err(ArgError::PrettyWithoutCanonicalize);
+    }
+    if output.is_some() && mode == CargoMode::Validate {
+        return Err(ArgError::OutputWithoutCanonicalize);
+    }
+    if precision.is_some() && mode == CargoMode::Validate {
+        return Err(ArgError::PrecisionWithoutCanonicalize);
+    }
+    if !final_newline && mode == CargoMode::Validate {
+        return Err(ArgError::NewlineWithoutCanonicalize);
+    }
+    if select.is_some() && mode == CargoMode::Validate {
+        return Err(ArgError::SelectWithoutCanonicalize);
+    }
+    if color.is_some() && mode == CargoMode::Validate {
+        return Err(ArgError::ColorWithoutCanonicalize);
+    }
+    if measure && mode == CargoMode::Validate {
+        return Err(ArgError::MeasureWithoutCanonicalize);
+    }
+    if report_all && (mode != CargoMode::Validate || stats) {
+        return Err(ArgError::ReportAllWithoutValidate);
+    }
+    if in_place && mode == CargoMode::Validate {
+        return Err(ArgError::InPlaceWithoutCanonicalize);
+    }
+    if in_place && input.is_none() && mode != CargoMode::Help {
+        return Err(ArgError::InPlaceWithoutFile);
+    }
+    if in_place && output.is_some() {
+        return Err(ArgError::InPlaceWithOutput);
+    }
+    if in_place && measure {
+        return Err(ArgError::InPlaceWithMeasure);
+    }
+    Ok(CargoArgs {
+        mode,
+        indent,
+        input,
+        output,
+        stats,
+        precision,
+        final_newline,
+        depth,
+        select,
+        color: color.unwrap_or(ColorChoice::Auto),
+        measure,
+        report_all,
+        in_place,
+    })
 }
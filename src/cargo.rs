@@ -1,12 +1,57 @@
+use crate::error::CargoError;
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
 use ascii::AsciiChar;
-use std::{
-    error::Error,
-    io::{self, BufReader, Stdin},
+#[cfg(feature = "bigdecimal")]
+use bigdecimal::{BigDecimal, ToPrimitive};
+#[cfg(feature = "bumpalo")]
+use bumpalo::Bump;
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{self, Peekable},
+    mem,
+    ops::Index,
+    slice,
+    str::{self, Chars, FromStr},
 };
+#[cfg(feature = "std")]
+use std::io::{self, BufReader, Read};
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug)]
-enum CargoValueType {
-    CargoNoType,
+/*
+ * Without the "std" feature there is no io::Read, so this stands in for it as the
+ * source of a CargoReader's input, providing only what the reader needs. It is
+ * implemented for byte slices, which are all that parse_slice reads from.
+ */
+#[cfg(not(feature = "std"))]
+pub trait Read {
+    fn read_cargo_byte(&mut self) -> Option<u8>;
+}
+
+#[cfg(not(feature = "std"))]
+impl Read for &[u8] {
+    fn read_cargo_byte(&mut self) -> Option<u8> {
+        let (&b, rest) = self.split_first()?;
+        *self = rest;
+        Some(b)
+    }
+}
+
+/*
+ * The type of a CargoValue, as recorded in its "cargo_type" field. There is no
+ * "no type" value, since every CargoValue has content of one of these types.
+ * Note that true, false and null are all of the basic type.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CargoValueType {
     CargoObjectType,
     CargoArrayType,
     CargoNumberType,
@@ -19,6 +64,24 @@ enum CargoValueType {
  * for a floating point value.
  */
 const CARGO_PRECISION: i32 = 15;
+const CARGO_MAX_PRECISION: usize = 17;
+
+/*
+ * The number of bytes of output that the io-based writers buffer by default
+ * before passing them on to the underlying writer.
+ */
+pub const CARGO_WRITE_BUFFER: usize = 8 << 10;
+
+/*
+ * The ANSI escape sequences written around each kind of token when WriteOpts
+ * asks for color: bold blue for member names, green for strings, cyan for
+ * numbers and magenta for true, false and null, each followed by a reset.
+ */
+const CARGO_COLOR_NAME: &str = "\x1b[1;34m";
+const CARGO_COLOR_STRING: &str = "\x1b[32m";
+const CARGO_COLOR_NUMBER: &str = "\x1b[36m";
+const CARGO_COLOR_LITERAL: &str = "\x1b[35m";
+const CARGO_COLOR_RESET: &str = "\x1b[0m";
 
 /*
  * Constants that define the tokens used to represent the basic values
@@ -28,6 +91,14 @@ const CARGO_TRUE_TOKEN: &str = "true";
 const CARGO_FALSE_TOKEN: &str = "false";
 const CARGO_NULL_TOKEN: &str = "null";
 
+/*
+ * Tokens that some producers emit for non-finite numbers, which the Cargo standard
+ * does not permit. They are only recognized so that they can be reported as
+ * invalid numbers rather than as unexpected characters.
+ */
+const CARGO_NAN_TOKEN: &str = "NaN";
+const CARGO_INFINITY_TOKEN: &str = "Infinity";
+
 const CARGO_COLON: char = AsciiChar::Colon.as_char();
 const CARGO_LBRACE: char = AsciiChar::CurlyBraceOpen.as_char();
 const CARGO_RBRACE: char = AsciiChar::CurlyBraceClose.as_char();
@@ -36,6 +107,8 @@ const CARGO_RBRACK: char = AsciiChar::BracketClose.as_char();
 const CARGO_QUOTE: char = AsciiChar::Quotation.as_char();
 const CARGO_BSLASH: char = AsciiChar::BackSlash.as_char();
 const CARGO_FSLASH: char = AsciiChar::Slash.as_char();
+const CARGO_STAR: char = AsciiChar::Asterisk.as_char();
+const CARGO_TILDE: char = AsciiChar::Tilde.as_char();
 const CARGO_COMMA: char = AsciiChar::Comma.as_char();
 const CARGO_PERIOD: char = AsciiChar::Dot.as_char();
 const CARGO_PLUS: char = AsciiChar::Plus.as_char();
@@ -55,254 +128,3767 @@ const CARGO_CR: char = AsciiChar::CarriageReturn.as_char();
 const CARGO_HT: char = AsciiChar::Tab.as_char();
 const CARGO_SPACE: char = AsciiChar::Space.as_char();
 
-trait WriteCargo {
-    fn write_cargo_cargo(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>>;
-}
+/*
+ * The byte order mark, which some editors put at the start of UTF-8 files even
+ * though the Cargo standard does not allow it.
+ */
+const CARGO_BOM: char = '\u{feff}';
 
-#[derive(Debug)]
-pub enum CargoContent {
-    Object(Box<CargoObject>),
-    Array(Box<CargoArray>),
-    String(CargoString),
-    Number(CargoNumber),
-    Basic(CargoBasic),
+/*
+ * Options that control what input the parser accepts. The defaults accept
+ * exactly what the Cargo standard permits, with the leniencies it allows.
+ * If "reject_duplicate_keys" is set, an object that contains two members with
+ * the same name is an error instead of the last of them winning.
+ * If "relaxed" is set, some input that the standard forbids is accepted as well:
+ * a comma may follow the last element of an array or member of an object, and
+ * comments in the style of C++ may appear wherever whitespace may, and a byte
+ * order mark at the very start of the input is ignored.
+ * If "iterative" is set, read_cargo_value builds the value without recursing
+ * into nested objects and arrays, so that no depth of nesting can exhaust the
+ * stack. The value is the same either way, but the recursive parser is faster.
+ * A string (including a member name) longer than "max_string_len" bytes once its
+ * escapes are decoded, or a number longer than "max_number_len" characters, is
+ * an error, detected as soon as the limit is passed rather than once the whole
+ * of it has been read. By default these are CARGO_MAX_STRING_LEN and
+ * CARGO_MAX_NUMBER_LEN, which are far beyond what any sensible input needs but
+ * stop a single huge token from exhausting memory.
+ * If "preserve_order" is set, as it is by default, the members of each object
+ * are kept in the order in which they appear in the input, so that they are
+ * written back out in that order. Otherwise they are sorted by name (comparing
+ * by Unicode code point, and keeping members with the same name in their
+ * original order), which gives the same output however the input was ordered.
+ * If "max_depth" is Some(n), an object or array nested within n others (so that
+ * n is the deepest nesting allowed, and Some(0) allows only a scalar) is an
+ * error, at the position of its opening bracket. By default it is Some of
+ * CARGO_MAX_DEPTH, so that the recursive parser cannot exhaust the stack, and
+ * None (which lifts the limit) is only safe with "iterative" as well.
+ * If "preserve_whitespace" is set, each value records the whitespace around it
+ * in the input (see CargoWhitespace), so that a writer with the option of the
+ * same name can reproduce the layout of the input. Comments are not recorded,
+ * and nor is anything by the iterative parser, which builds values from events.
+ */
+#[derive(Debug, Clone)]
+pub struct ParseConfig {
+    pub reject_duplicate_keys: bool,
+    pub relaxed: bool,
+    pub iterative: bool,
+    pub max_string_len: usize,
+    pub max_number_len: usize,
+    pub preserve_order: bool,
+    pub max_depth: Option<usize>,
+    pub preserve_whitespace: bool,
 }
 
-impl WriteCargo for CargoContent {
-    fn write_cargo_cargo(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        match &self {
-            CargoContent::Object(object) => object.write_cargo_object(r),
-            CargoContent::Array(array) => array.write_cargo_array(r),
-            CargoContent::String(string) => string.write_cargo_string(r),
-            CargoContent::Number(number) => number.write_cargo_number(r),
-            CargoContent::Basic(basic) => basic.write_cargo_basic(r),
-            _ => Ok(()),
+pub const CARGO_MAX_STRING_LEN: usize = 64 << 20;
+pub const CARGO_MAX_NUMBER_LEN: usize = 1024;
+pub const CARGO_MAX_DEPTH: usize = 128;
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            reject_duplicate_keys: false,
+            relaxed: false,
+            iterative: false,
+            max_string_len: CARGO_MAX_STRING_LEN,
+            max_number_len: CARGO_MAX_NUMBER_LEN,
+            preserve_order: true,
+            max_depth: Some(CARGO_MAX_DEPTH),
+            preserve_whitespace: false,
         }
     }
 }
 
 /*
- * Structure used to hold a string value.
- * The content field is maintained as an array of char, which is not null-terminated
- * and which might contain '\0' characters. This data is interpreted as Unicode text,
- * represented as an array of CargoChar values, each of which represents a single
- * Unicode code point. The length field gives the length in bytes of the data.
- * The capacity field records the actual size of the data area. This is included so
- * that the size can be dynamically increased while the string is being read.
+ * Each option can also be set by a method of the same name, so that a config
+ * can be built up in a single expression, as in
+ * ParseConfig::new().relaxed(true).max_string_len(1024).
  */
-#[derive(Debug)]
-pub struct CargoString {
-    capacity: usize,
-    length: usize,
-    content: String,
+impl ParseConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn reject_duplicate_keys(mut self, reject_duplicate_keys: bool) -> Self {
+        self.reject_duplicate_keys = reject_duplicate_keys;
+        self
+    }
+    pub fn relaxed(mut self, relaxed: bool) -> Self {
+        self.relaxed = relaxed;
+        self
+    }
+    pub fn iterative(mut self, iterative: bool) -> Self {
+        self.iterative = iterative;
+        self
+    }
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = max_string_len;
+        self
+    }
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.max_number_len = max_number_len;
+        self
+    }
+    pub fn preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+    pub fn preserve_whitespace(mut self, preserve_whitespace: bool) -> Self {
+        self.preserve_whitespace = preserve_whitespace;
+        self
+    }
 }
 
-impl CargoString {
-    fn new(capacity: usize, length: usize, content: String) -> Self {
+/*
+ * The source of the characters that the read_cargo_* functions parse, one at a
+ * time with a single character of lookahead. It also keeps track of the line and
+ * column of the next character to be read, so that errors can report where in the
+ * input they were detected, and holds the ParseConfig that the functions consult.
+ * A parse that may need to be abandoned can call mark first, and then reset to
+ * read the same characters again from the marked position, which is also where
+ * pos reports them to be. Marking again moves the mark, and the mark is kept after
+ * a reset, so that the same position may be returned to more than once. A reader
+ * need not hold on to more than CARGO_MAX_LOOKAHEAD characters read past the mark
+ * (as a CargoReader, which buffers them, does not), and resetting a reader that
+ * has let its mark go, or that was never marked, is a MarkLost error.
+ */
+pub trait CargoRead {
+    fn peek(&mut self) -> Result<Option<char>, CargoError>;
+    fn next(&mut self) -> Result<Option<char>, CargoError>;
+    fn pos(&self) -> (usize, usize);
+    fn config(&self) -> &ParseConfig;
+    fn mark(&mut self);
+    fn reset(&mut self) -> Result<(), CargoError>;
+}
+
+pub const CARGO_MAX_LOOKAHEAD: usize = 4096;
+
+/*
+ * The position just after the character "c", which is at "pos".
+ */
+fn advance_cargo_pos((line, column): (usize, usize), c: char) -> (usize, usize) {
+    if c == CARGO_LF {
+        (line + 1, 1)
+    } else {
+        (line, column + 1)
+    }
+}
+
+/*
+ * The CargoReader wraps a UTF-8 input stream in a buffer and hands out its
+ * contents as a CargoRead, decoding the characters as it goes.
+ * While it is marked, "marked" holds the position of the mark and the characters
+ * read since, and after a reset, "replayed" holds those that are to be read again,
+ * the next of them last.
+ */
+pub struct CargoReader<R: Read> {
+    #[cfg(feature = "std")]
+    bytes: io::Bytes<BufReader<R>>,
+    #[cfg(not(feature = "std"))]
+    bytes: R,
+    peeked: Option<char>,
+    marked: Option<((usize, usize), Vec<char>)>,
+    replayed: Vec<char>,
+    line: usize,
+    column: usize,
+    config: ParseConfig,
+}
+
+impl<R: Read> CargoReader<R> {
+    pub fn new(r: R) -> Self {
+        Self::with_config(r, ParseConfig::default())
+    }
+    pub fn with_config(r: R, config: ParseConfig) -> Self {
         Self {
-            capacity,
-            length,
-            content,
+            #[cfg(feature = "std")]
+            bytes: BufReader::new(r).bytes(),
+            #[cfg(not(feature = "std"))]
+            bytes: r,
+            peeked: None,
+            marked: None,
+            replayed: Vec::new(),
+            line: 1,
+            column: 1,
+            config,
         }
     }
-    fn append_char(&mut self, c: char) {
-        self.content.push(c);
-        self.length += 1;
+    /*
+     * The next byte of input, or None at its end. A read that is interrupted is
+     * retried by io::Bytes, and one that returns fewer bytes than asked for is
+     * simply followed by another, so only a read that returns no bytes at all is
+     * taken as the end; if that comes in the middle of a token, the caller reports
+     * it as UnexpectedEof. Any other failure is returned as CargoError::Io.
+     */
+    #[cfg(feature = "std")]
+    fn next_byte(&mut self) -> Result<Option<u8>, CargoError> {
+        Ok(self.bytes.next().transpose()?)
     }
-    fn write_cargo_string(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        let cs: Self = Self {
-            capacity: 0,
-            length: 0,
-            content: String::new(),
+    #[cfg(not(feature = "std"))]
+    fn next_byte(&mut self) -> Result<Option<u8>, CargoError> {
+        Ok(self.bytes.read_cargo_byte())
+    }
+    fn peek_cargo_char(&mut self) -> Result<Option<char>, CargoError> {
+        if self.peeked.is_none() {
+            self.peeked = match self.replayed.pop() {
+                Some(c) => Some(c),
+                None => match self.next_byte()? {
+                    Some(b) => Some(self.decode_cargo_char(b)?),
+                    None => None,
+                },
+            };
+        }
+        Ok(self.peeked)
+    }
+    /*
+     * The input is UTF-8, so a byte outside the ASCII range starts a sequence of
+     * two to four bytes that together encode a single character. Any sequence that
+     * is not valid UTF-8 (including overlong forms and encoded surrogates) is an
+     * error at the position of the character it should have encoded.
+     */
+    fn decode_cargo_char(&mut self, first: u8) -> Result<char, CargoError> {
+        let at = self.pos();
+        let len = match first {
+            0x00..=0x7f => return Ok(char::from(first)),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(CargoError::InvalidUtf8 { at }),
         };
-        Ok(())
+        let mut buf = [first, 0, 0, 0];
+        for b in &mut buf[1..len] {
+            *b = self.next_byte()?.ok_or(CargoError::InvalidUtf8 { at })?;
+        }
+        str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or(CargoError::InvalidUtf8 { at })
     }
 }
 
-fn read_cargo_string(r: BufReader<Stdin>) -> Result<CargoString, Box<dyn Error>> {
-    Ok(CargoString {
-        capacity: 10,
-        length: 10,
-        content: String::new(),
-    })
+impl<R: Read> CargoRead for CargoReader<R> {
+    fn peek(&mut self) -> Result<Option<char>, CargoError> {
+        self.peek_cargo_char()
+    }
+    fn next(&mut self) -> Result<Option<char>, CargoError> {
+        let c = self.peek_cargo_char()?;
+        if let Some(c) = self.peeked.take() {
+            (self.line, self.column) = advance_cargo_pos(self.pos(), c);
+            if let Some((_, read)) = &mut self.marked {
+                read.push(c);
+                if read.len() > CARGO_MAX_LOOKAHEAD {
+                    self.marked = None;
+                }
+            }
+        }
+        Ok(c)
+    }
+    fn pos(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+    fn config(&self) -> &ParseConfig {
+        &self.config
+    }
+    fn mark(&mut self) {
+        self.marked = Some((self.pos(), Vec::new()));
+    }
+    /*
+     * The characters read since the mark go back in front of any that were peeked
+     * at or are still to be replayed from an earlier reset.
+     */
+    fn reset(&mut self) -> Result<(), CargoError> {
+        let at = self.pos();
+        let (at, read) = self.marked.as_mut().ok_or(CargoError::MarkLost { at })?;
+        self.replayed.extend(self.peeked.take());
+        self.replayed.extend(read.drain(..).rev());
+        (self.line, self.column) = *at;
+        Ok(())
+    }
 }
 
 /*
- * Structure used to hold a number.
- * The "text_value" field holds a printable/parseable representation of the number
- * as Unicode text, conforming to the Argo standard.
- * The "int_value" field holds the value of the number in integer format, if the
- * number can be exactly represented as such.
- * The "float_value" field holds the value of the number in floating-point format.
- *
- * If multiple representations of the value of the number are present, they should
- * agree with each other.
- * It is up to an application to determine which representation is the appropriate
- * one to use, based on the semantics of the data being represented.
+ * A CargoStrReader hands out the characters of text that is already in memory,
+ * which needs neither buffering nor decoding, and cannot fail to be read.
  */
+pub struct CargoStrReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    marked: Option<(Peekable<Chars<'a>>, (usize, usize))>,
+    line: usize,
+    column: usize,
+    config: ParseConfig,
+}
 
-#[derive(Debug)]
-pub struct CargoNumber {
-    string_value: Option<CargoString>,
-    int_value: Option<u64>,
-    float_value: Option<f64>,
+impl<'a> CargoStrReader<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::with_config(s, ParseConfig::default())
+    }
+    pub fn with_config(s: &'a str, config: ParseConfig) -> Self {
+        Self {
+            chars: s.chars().peekable(),
+            marked: None,
+            line: 1,
+            column: 1,
+            config,
+        }
+    }
 }
 
-impl CargoNumber {
-    fn write_cargo_number(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
+impl CargoRead for CargoStrReader<'_> {
+    fn peek(&mut self) -> Result<Option<char>, CargoError> {
+        Ok(self.chars.peek().copied())
+    }
+    fn next(&mut self) -> Result<Option<char>, CargoError> {
+        let c = self.chars.next();
+        if let Some(c) = c {
+            (self.line, self.column) = advance_cargo_pos(self.pos(), c);
+        }
+        Ok(c)
+    }
+    fn pos(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+    fn config(&self) -> &ParseConfig {
+        &self.config
+    }
+    fn mark(&mut self) {
+        self.marked = Some((self.chars.clone(), self.pos()));
+    }
+    fn reset(&mut self) -> Result<(), CargoError> {
+        let (chars, at) = self
+            .marked
+            .clone()
+            .ok_or(CargoError::MarkLost { at: self.pos() })?;
+        self.chars = chars;
+        (self.line, self.column) = at;
         Ok(())
     }
 }
 
-fn read_cargo_number(r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-    Ok(())
+/*
+ * Checks that an object or array that starts with the next character, and so
+ * is at the given depth of nesting, is within the configured max_depth.
+ */
+fn check_cargo_depth<R: CargoRead>(r: &R, depth: usize) -> Result<(), CargoError> {
+    match r.config().max_depth {
+        Some(max_depth) if depth > max_depth => Err(CargoError::DepthExceeded { at: r.pos() }),
+        _ => Ok(()),
+    }
 }
 
 /*
- * Basic Cargo values, represented by the (unquoted) tokens
- * "true", "false", or "null" in Cargo code.
+ * Builds the error for a character (or end of input) that was found where
+ * something else was expected.
  */
-#[derive(Debug)]
-pub enum CargoBasic {
-    CargoNull,
-    CargoTrue(bool),
-    CargoFalse(bool),
+fn cargo_unexpected(found: Option<char>, at: (usize, usize)) -> CargoError {
+    match found {
+        Some(c) => CargoError::UnexpectedChar { found: c, at },
+        None => CargoError::UnexpectedEof { at },
+    }
 }
 
-impl CargoBasic {
-    fn write_cargo_basic(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        Ok(())
+/*
+ * Checks that the input goes on within the object or array opened at the given
+ * position, so that running out of input there is reported against its opener
+ * rather than the end of the input.
+ */
+fn expect_cargo_more<R: CargoRead>(r: &mut R, opened_at: (usize, usize)) -> Result<(), CargoError> {
+    match r.peek()? {
+        Some(_) => Ok(()),
+        None => Err(CargoError::UnterminatedContainer { opened_at }),
+    }
+}
+
+fn expect_cargo_char<R: CargoRead>(r: &mut R, expected: char) -> Result<(), CargoError> {
+    let at = r.pos();
+    match r.next()? {
+        Some(c) if c == expected => Ok(()),
+        found => Err(cargo_unexpected(found, at)),
     }
 }
-fn read_cargo_basic(r: BufReader<Stdin>) -> Result<CargoBasic, Box<dyn Error>> {
-    Ok(CargoBasic::CargoTrue(true))
+
+fn skip_cargo_whitespace<R: CargoRead>(r: &mut R) -> Result<(), CargoError> {
+    read_cargo_whitespace(r, None)
 }
 
 /*
- * An "array" has an ordered sequence of elements, each of which is just a value.
- * Here we represent the elements as a circular, doubly linked list, in the same
- * way as for the members of an object.  The "element_list" field in the CargoArray
- * structure serves as the sentinel at the head of the list.
- *
- * Note that elements of an array do not have any name, so the "name" field in each
- * of the elements will be NULL.  Arrays could be represented as actual arrays,
- * but we are not doing that here.
+ * Skips whitespace in the same way as skip_cargo_whitespace, but returns the
+ * whitespace characters that were skipped (though not any comments) if they are
+ * to be preserved. Otherwise the result is empty.
  */
-#[derive(Debug)]
-pub struct CargoArray {
-    element_list: Option<CargoValue>,
+fn read_cargo_space<R: CargoRead>(r: &mut R) -> Result<String, CargoError> {
+    let mut space = String::new();
+    let keep = r.config().preserve_whitespace.then_some(&mut space);
+    read_cargo_whitespace(r, keep)?;
+    Ok(space)
 }
 
-impl CargoArray {
-    fn write_cargo_array(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        Ok(())
+fn read_cargo_whitespace<R: CargoRead>(
+    r: &mut R,
+    mut keep: Option<&mut String>,
+) -> Result<(), CargoError> {
+    loop {
+        match r.peek()? {
+            Some(c) if cargo_is_whitespace(c) => {
+                if let Some(space) = keep.as_mut() {
+                    space.push(c);
+                }
+                r.next()?;
+            }
+            Some(CARGO_FSLASH) if r.config().relaxed => skip_cargo_comment(r)?,
+            Some(CARGO_BOM) if r.pos() == (1, 1) => {
+                if !r.config().relaxed {
+                    return Err(CargoError::ByteOrderMark);
+                }
+                r.next()?;
+            }
+            _ => return Ok(()),
+        }
     }
 }
-fn read_cargo_array(r: BufReader<Stdin>) -> Result<CargoArray, Box<dyn Error>> {
-    Ok(CargoArray {
-        element_list: Option::None,
-    })
-}
 
 /*
- * An "object" has a list of members, each of which has a name and a value.
- * To store the members, we use a circular, doubly linked list, with the next and
- * previous pointers stored in the "next" and "prev" fields of the ARGO_VALUE structure
- * and the member name stored in the "name" field of the ARGO_VALUE structure.
- * The "member_list" field of the ARGO_OBJECT structure serves as the sentinel at
- * the head of the list.  This element does not represent one of the members;
- * rather, its "next" field points to the first member and its "prev" field points
- * to the last member.  An empty list of members is represented by the situation in
- * which both the "next" and "prev" fields point back to the sentinel object itself.
- *
- * Note that the collection of members of an object is supposed to be regarded as unordered,
- * which would permit it to be represented using a hash map or similar data structure,
- * which we are not doing here.
+ * Skips a comment, which is only permitted in relaxed mode. A line comment starts
+ * with two slashes and runs to the end of the line, while a block comment starts
+ * with a slash and a star and runs to the next star and slash. A block comment
+ * that is still open at the end of the input is an error.
  */
-#[derive(Debug)]
-pub struct CargoObject {
-    member_list: Option<CargoValue>,
+fn skip_cargo_comment<R: CargoRead>(r: &mut R) -> Result<(), CargoError> {
+    expect_cargo_char(r, CARGO_FSLASH)?;
+    let at = r.pos();
+    match r.next()? {
+        Some(CARGO_FSLASH) => {
+            while let Some(c) = r.next()? {
+                if c == CARGO_LF {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        Some(CARGO_STAR) => {
+            let mut star = false;
+            loop {
+                let at = r.pos();
+                match r.next()? {
+                    Some(CARGO_FSLASH) if star => return Ok(()),
+                    Some(c) => star = c == CARGO_STAR,
+                    None => return Err(CargoError::UnexpectedEof { at }),
+                }
+            }
+        }
+        found => Err(cargo_unexpected(found, at)),
+    }
 }
 
-impl CargoObject {
-    fn write_cargo_object(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        Ok(())
+/*
+ * In relaxed mode, a comma may be followed directly by the closing bracket or
+ * brace of the array or object. Once a comma has been read, this consumes such
+ * a closing character, if there is one, and returns whether it did.
+ */
+fn read_cargo_trailing_close<R: CargoRead>(r: &mut R, close: char) -> Result<bool, CargoError> {
+    if !r.config().relaxed {
+        return Ok(false);
     }
+    skip_cargo_whitespace(r)?;
+    if r.peek()? == Some(close) {
+        r.next()?;
+        return Ok(true);
+    }
+    Ok(false)
 }
-pub fn read_cargo_object(r: BufReader<Stdin>) -> Result<CargoObject, Box<dyn Error>> {
-    Ok(CargoObject {
-        member_list: Option::None,
-    })
+
+/*
+ * Skips any whitespace and returns whether there is more input after it, for
+ * reading a sequence of top-level values one after another.
+ */
+pub fn read_cargo_more<R: CargoRead>(r: &mut R) -> Result<bool, CargoError> {
+    skip_cargo_whitespace(r)?;
+    Ok(r.peek()?.is_some())
 }
 
 /*
- * The CargoValue structure is used to represent all kinds of Argo values.
- * The "type" field tells what type of value it represents.
- * It has "next" and "prev" fields so that it can be linked into "members"
- * or "elements" lists.  It has a "name" field which will hold the name in case
- * it is a member of an object.  The "content" field is the union of the structures
- * that represent the various Cargo types.  Depending on the value of the "type" field,
- * one of the "object", "array", or "string", "number", or "basic" variants of this union
- * will be valid.
+ * Skips any whitespace that follows the top-level value, however many spaces,
+ * tabs and line endings (LF or CRLF) there are, and checks that the end of the
+ * input has been reached. Anything else, such as a stray closing bracket or the
+ * start of a second value, is an UnexpectedChar error at its position.
  */
-#[derive(Debug)]
-pub struct CargoValue {
-    cargo_type: CargoValueType,
-    name: CargoString,
-    content: CargoContent,
+pub fn read_cargo_eof<R: CargoRead>(r: &mut R) -> Result<(), CargoError> {
+    skip_cargo_whitespace(r)?;
+    match r.peek()? {
+        None => Ok(()),
+        Some(c) => Err(CargoError::UnexpectedChar {
+            found: c,
+            at: r.pos(),
+        }),
+    }
 }
 
-impl CargoValue {
-    pub fn new(_type: CargoValueType, name: String) -> Self {
+/*
+ * Options that control the form in which values are written.
+ * If "indent" is None, the output is compact and contains no whitespace except
+ * within strings. Otherwise the output is pretty-printed, with the given indent
+ * output at the beginning of each line for each level of indentation.
+ * If "verbatim_numbers" is set, numbers that were read from input are written
+ * exactly as they appeared there rather than being reformatted, so that no
+ * precision is lost.
+ * If "collapse_threshold" is Some(n) when pretty-printing, an array or object
+ * that has fewer than n elements or members, all of which are simple, is written
+ * on a single line, as in [1, 2, 3]. Strings, numbers, true, false and null are
+ * simple, while objects and arrays (even empty ones) are not.
+ * If "sort_keys" is set, the members of each object are written in order of
+ * their names (comparing by Unicode code point) rather than in the order in
+ * which they were read. Members with the same name keep their relative order.
+ * The last two options need the whole of each object or array to be known in
+ * advance, so they are ignored by write_cargo_events.
+ * If "precision" is Some(n), numbers that are not integers are written with at
+ * most n significant digits instead of CARGO_PRECISION. Since an f64 never needs
+ * more than 17 digits, n is taken to be between 1 and 17.
+ * If "integers_as_floats" is set, integers are written with a fractional part of
+ * zero, as in 5.0, for readers that want every number to look like a float (any
+ * other number already has a decimal point). The digits are still those of the
+ * exact integer, so one too large for an f64 to hold, such as 2^53 + 1, is rounded
+ * by the reader as it would have been without the ".0". Numbers written verbatim
+ * are left as they were read.
+ * If "final_newline" is Some(b), a single newline is written after the top-level
+ * value if b is true and not otherwise. If it is None, the newline is written
+ * only when pretty-printing, since compact output contains no whitespace.
+ * "line_ending" is the sequence written for each newline, whether between the
+ * lines of pretty-printed output or at the end.
+ * If "preserve_whitespace" is set, each value that recorded the whitespace around
+ * it when it was read (with the ParseConfig option of the same name) is written
+ * with that whitespace instead of what "indent" calls for, and values that did not
+ * are written as usual. With "verbatim_numbers" as well, input whose strings are
+ * already in canonical form is reproduced exactly. Like "sort_keys", this option
+ * is ignored by write_cargo_events.
+ * If "ascii_only" is set, the output is pure ASCII: the characters of Latin-1
+ * that are normally written as they are (such as "é") are written as \u escapes
+ * too, like everything beyond Latin-1 always is.
+ * If "normalize_unicode" is set (which needs the "unicode-normalization" feature),
+ * strings and names are put in Unicode Normalization Form C before they are
+ * written, so that text that differs only in how its accented characters are
+ * composed, such as "\u00e9" and "e\u0301", is written the same way.
+ * If "buffer_size" is Some(n), the writers that take an io::Write (such as
+ * to_writer and write_cargo_events) buffer up to n bytes of output instead of
+ * CARGO_WRITE_BUFFER. Some(0) passes each piece straight to the writer.
+ * If "color" is set, member names, strings, numbers and the literals are each
+ * wrapped in ANSI escape sequences that show them in a color of their own, for
+ * display on a terminal. The output is then no longer valid JSON.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct WriteOpts {
+    pub indent: Option<IndentStyle>,
+    pub verbatim_numbers: bool,
+    pub collapse_threshold: Option<usize>,
+    pub sort_keys: bool,
+    pub precision: Option<usize>,
+    pub integers_as_floats: bool,
+    pub final_newline: Option<bool>,
+    pub line_ending: LineEnding,
+    pub ascii_only: bool,
+    pub preserve_whitespace: bool,
+    pub buffer_size: Option<usize>,
+    pub color: bool,
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_unicode: bool,
+}
+
+impl WriteOpts {
+    fn pretty(indent: u32) -> Self {
         Self {
-            cargo_type: _type,
-            name: CargoString {
-                capacity: name.capacity(),
-                length: name.len(),
-                content: name,
-            },
-            content: match _type {
-                CargoValueType::CargoObjectType | _ => {
-                    CargoContent::Object(Box::new(CargoObject {
-                        member_list: Option::None,
-                    }))
-                }
-                CargoValueType::CargoArrayType => CargoContent::Array(Box::new(CargoArray {
-                    element_list: Option::None,
-                })),
-            },
+            indent: Some(IndentStyle::Spaces(indent)),
+            ..Self::default()
         }
     }
-    fn write_cargo_object(&self, r: BufReader<Stdin>) -> Result<(), Box<dyn Error>> {
-        Ok(())
+    fn final_newline(&self) -> bool {
+        self.final_newline.unwrap_or(self.indent.is_some())
+    }
+    #[cfg(feature = "std")]
+    fn buffer_size(&self) -> usize {
+        self.buffer_size.unwrap_or(CARGO_WRITE_BUFFER)
     }
 }
 
-pub fn read_cargo_value() -> io::Result<CargoValue> {
-    Ok(CargoValue::new(
-        CargoValueType::CargoObjectType,
-        "Sentinel".to_string(),
-    ))
+/*
+ * Calls "write" to write a token that is to be shown in the given color, with the
+ * escape sequences for the color around it if the opts call for them.
+ */
+fn write_cargo_colored<W: fmt::Write>(
+    w: &mut W,
+    opts: &WriteOpts,
+    color: &str,
+    write: impl FnOnce(&mut W) -> fmt::Result,
+) -> fmt::Result {
+    if !opts.color {
+        return write(w);
+    }
+    w.write_str(color)?;
+    write(w)?;
+    w.write_str(CARGO_COLOR_RESET)
 }
 
-fn cargo_is_whitespace(c: char) -> bool {
-    c == CARGO_SPACE || c == CARGO_LF || c == CARGO_CR || c == CARGO_HT
+trait WriteCargo {
+    fn write_cargo<W: fmt::Write>(&self, w: &mut W, opts: &WriteOpts, depth: usize) -> fmt::Result;
 }
 
-fn cargo_is_exponent(c: char) -> bool {
-    c == CARGO_E || c == AsciiChar::E.as_char()
+/*
+ * Writes the elements of an array or the members of an object, separated by
+ * commas, between the given opening and closing characters. Unless the container
+ * is collapsed onto one line, each is on a line of its own when pretty-printing.
+ * If "inside" is Some, it is the preserved whitespace to write before the closing
+ * character.
+ */
+fn write_cargo_container<'a, W: fmt::Write>(
+    w: &mut W,
+    opts: &WriteOpts,
+    depth: usize,
+    values: impl ExactSizeIterator<Item = &'a CargoValue> + Clone,
+    (open, close): (char, char),
+    inside: Option<&str>,
+) -> fmt::Result {
+    let collapse = opts.indent.is_some()
+        && opts.collapse_threshold.is_some_and(|n| {
+            values.len() < n
+                && values.clone().all(|value| {
+                    !matches!(
+                        value.content,
+                        CargoContent::Object(_) | CargoContent::Array(_)
+                    )
+                })
+        });
+    w.write_char(open)?;
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            w.write_char(CARGO_COMMA)?;
+        }
+        if let Some(space) = value.cargo_whitespace(opts) {
+            w.write_str(&space.before)?;
+        } else if collapse && i > 0 {
+            w.write_char(CARGO_SPACE)?;
+        } else if !collapse {
+            write_cargo_newline(w, opts, depth + 1)?;
+        }
+        value.write_cargo_member(w, opts, depth + 1)?;
+        if let Some(space) = value.cargo_whitespace(opts) {
+            w.write_str(&space.after)?;
+        }
+    }
+    match inside {
+        Some(inside) => w.write_str(inside)?,
+        None if !collapse => write_cargo_newline(w, opts, depth)?,
+        None => {}
+    }
+    w.write_char(close)
 }
 
-fn cargo_is_digit(c: char) -> bool {
-    c >= CARGO_DIGIT0 || c <= AsciiChar::_9.as_char()
+/*
+ * The indent for each level of a pretty-printed value: either a number of spaces
+ * (possibly zero, in which case lines are not indented at all) or a single tab.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces(u32),
+    Tabs,
 }
 
-fn cargo_is_hex(c: char) -> bool {
-    cargo_is_digit(c)
-        || (c >= AsciiChar::A.as_char() && c <= AsciiChar::F.as_char())
-        || (c >= AsciiChar::a.as_char() && c <= AsciiChar::f.as_char())
+/*
+ * The sequence that ends each line of output: a line feed alone, or a carriage
+ * return followed by a line feed as is usual on Windows.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
 }
 
-fn cargo_is_control(c: char) -> bool {
-    c >= AsciiChar::Null.as_char() && c < CARGO_SPACE
+impl LineEnding {
+    fn write_cargo_line_ending<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        if *self == LineEnding::Crlf {
+            w.write_char(CARGO_CR)?;
+        }
+        w.write_char(CARGO_LF)
+    }
+}
+
+/*
+ * Writes a newline followed by the indentation for the given depth, if the output
+ * is being pretty-printed. Nothing is written in compact mode.
+ */
+fn write_cargo_newline<W: fmt::Write>(w: &mut W, opts: &WriteOpts, depth: usize) -> fmt::Result {
+    let (c, count) = match opts.indent {
+        None => return Ok(()),
+        Some(IndentStyle::Spaces(n)) => (CARGO_SPACE, depth * n as usize),
+        Some(IndentStyle::Tabs) => (CARGO_HT, depth),
+    };
+    opts.line_ending.write_cargo_line_ending(w)?;
+    for _ in 0..count {
+        w.write_char(c)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CargoContent {
+    Object(Box<CargoObject>),
+    Array(Box<CargoArray>),
+    String(CargoString),
+    Number(CargoNumber),
+    Basic(CargoBasic),
+}
+
+impl WriteCargo for CargoContent {
+    fn write_cargo<W: fmt::Write>(&self, w: &mut W, opts: &WriteOpts, depth: usize) -> fmt::Result {
+        match &self {
+            CargoContent::Object(object) => object.write_cargo_object(w, opts, depth, None),
+            CargoContent::Array(array) => array.write_cargo_array(w, opts, depth, None),
+            CargoContent::String(string) => write_cargo_colored(w, opts, CARGO_COLOR_STRING, |w| {
+                string.write_cargo_string(w, opts)
+            }),
+            CargoContent::Number(number) => write_cargo_colored(w, opts, CARGO_COLOR_NUMBER, |w| {
+                number.write_cargo_number(w, opts)
+            }),
+            CargoContent::Basic(basic) => {
+                write_cargo_colored(w, opts, CARGO_COLOR_LITERAL, |w| basic.write_cargo_basic(w))
+            }
+        }
+    }
+}
+
+/*
+ * Typed accessors, each of which returns None if the content is not of the
+ * requested type. A number has an f64 value whether or not it was written as an
+ * integer, but it only has an i64 value if it was written as an integer that fits.
+ */
+impl CargoContent {
+    pub fn as_object(&self) -> Option<&CargoObject> {
+        match self {
+            CargoContent::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[CargoValue]> {
+        match self {
+            CargoContent::Array(array) => Some(&array.element_list),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CargoContent::String(string) => Some(string.as_str()),
+            _ => None,
+        }
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            CargoContent::Number(number) => number.as_i64(),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CargoContent::Number(number) => number.as_f64(),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CargoContent::Basic(CargoBasic::CargoTrue(b) | CargoBasic::CargoFalse(b)) => Some(*b),
+            _ => None,
+        }
+    }
+    /*
+     * Looks up a member by name if the content is an object; see CargoObject::get.
+     */
+    pub fn get(&self, name: &str) -> Option<&CargoValue> {
+        self.as_object().and_then(|object| object.get(name))
+    }
+    /*
+     * The name of the type of the content, as for CargoValue::type_name.
+     */
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            CargoContent::Object(_) => "object",
+            CargoContent::Array(_) => "array",
+            CargoContent::String(_) => "string",
+            CargoContent::Number(_) => "number",
+            CargoContent::Basic(CargoBasic::CargoNull) => "null",
+            CargoContent::Basic(_) => "boolean",
+        }
+    }
+    /*
+     * Counterparts of the accessors above that take the content and return what
+     * it holds without copying it, or a TypeMismatch if it is of another type.
+     */
+    pub fn into_object(self) -> Result<CargoObject, CargoError> {
+        match self {
+            CargoContent::Object(object) => Ok(*object),
+            other => Err(cargo_content_mismatch("object", &other)),
+        }
+    }
+    pub fn into_array(self) -> Result<Vec<CargoValue>, CargoError> {
+        match self {
+            CargoContent::Array(mut array) => Ok(mem::take(&mut array.element_list)),
+            other => Err(cargo_content_mismatch("array", &other)),
+        }
+    }
+    pub fn into_string(self) -> Result<String, CargoError> {
+        match self {
+            CargoContent::String(string) => Ok(string.content),
+            other => Err(cargo_content_mismatch("string", &other)),
+        }
+    }
+    pub fn into_number(self) -> Result<CargoNumber, CargoError> {
+        match self {
+            CargoContent::Number(number) => Ok(number),
+            other => Err(cargo_content_mismatch("number", &other)),
+        }
+    }
+}
+
+/*
+ * Values are displayed in compact canonical form, exactly as written by -c.
+ */
+impl fmt::Display for CargoContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_cargo(f, &WriteOpts::default(), 0)
+    }
+}
+
+/*
+ * Structure used to hold a string value.
+ * The content field is maintained as an array of char, which is not null-terminated
+ * and which might contain '\0' characters (though in the input a NUL can only
+ * appear as the escape \u0000, which is also how it is written out again, since
+ * it is a control character). This data is interpreted as Unicode text,
+ * represented as an array of CargoChar values, each of which represents a single
+ * Unicode code point. The length field gives the length in bytes of the data, as
+ * encoded in UTF-8, so it is the same as content.len() and not, in general, the
+ * number of characters. The capacity field records the actual size of the data
+ * area, which is the capacity of the content String. This is included so that the
+ * size can be dynamically increased while the string is being read: when a
+ * character does not fit, append_char reserves room for at least
+ * CARGO_STRING_CHUNK more bytes, or as many as the string already holds if that
+ * is more, so that a long string is reallocated only a few times.
+ */
+const CARGO_STRING_CHUNK: usize = 64;
+
+#[derive(Debug)]
+pub struct CargoString {
+    capacity: usize,
+    length: usize,
+    content: String,
+}
+
+impl CargoString {
+    fn new(capacity: usize, length: usize, content: String) -> Self {
+        Self {
+            capacity,
+            length,
+            content,
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+    /*
+     * The length of the string in bytes (not characters).
+     */
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    fn append_char(&mut self, c: char) {
+        let len = c.len_utf8();
+        if self.capacity - self.length < len {
+            // The growth saturates rather than overflowing; a size that cannot be
+            // allocated is then reported by reserve as for any other String.
+            let grow = self.capacity.max(CARGO_STRING_CHUNK);
+            let wanted = self.capacity.saturating_add(grow);
+            self.content.reserve(wanted - self.length);
+            self.capacity = self.content.capacity();
+        }
+        self.content.push(c);
+        self.length += len;
+    }
+    /*
+     * Writes the string as a quoted literal. Quotes and backslashes are escaped,
+     * control characters that have a special escape are written using it, and all
+     * other control characters (including DEL and those from U+0080 to U+009F), the
+     * soft hyphen U+00AD, and characters above U+00FF are written as \u escapes
+     * with lower-case hex digits (using a surrogate pair for characters outside the
+     * Basic Multilingual Plane). Everything else, which is printable ASCII and the
+     * rest of Latin-1, appears as itself. So however a character was written in
+     * the input, whether escaped or not, it has only one form in the output.
+     */
+    fn write_cargo_string<W: fmt::Write>(&self, w: &mut W, opts: &WriteOpts) -> fmt::Result {
+        #[cfg(feature = "unicode-normalization")]
+        if opts.normalize_unicode {
+            return write_cargo_chars(w, self.content.nfc(), opts);
+        }
+        write_cargo_chars(w, self.content.chars(), opts)
+    }
+}
+
+/*
+ * Writes the characters as a quoted literal, for CargoString::write_cargo_string.
+ */
+fn write_cargo_chars<W: fmt::Write>(
+    w: &mut W,
+    chars: impl Iterator<Item = char>,
+    opts: &WriteOpts,
+) -> fmt::Result {
+    w.write_char(CARGO_QUOTE)?;
+    for c in chars {
+        let escape = match c {
+            CARGO_QUOTE | CARGO_BSLASH => Some(c),
+            CARGO_BS => Some(CARGO_B),
+            CARGO_FF => Some(CARGO_F),
+            CARGO_LF => Some(CARGO_N),
+            CARGO_CR => Some(CARGO_R),
+            CARGO_HT => Some(CARGO_T),
+            _ => None,
+        };
+        if let Some(e) = escape {
+            w.write_char(CARGO_BSLASH)?;
+            w.write_char(e)?;
+        } else if cargo_needs_unicode_escape(c) || (opts.ascii_only && !c.is_ascii()) {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(w, "\\u{:04x}", unit)?;
+            }
+        } else {
+            w.write_char(c)?;
+        }
+    }
+    w.write_char(CARGO_QUOTE)
+}
+
+impl From<String> for CargoString {
+    fn from(content: String) -> Self {
+        Self::new(content.capacity(), content.len(), content)
+    }
+}
+
+/*
+ * A clone of the content has only as much room as it needs, so the capacity is
+ * taken from it rather than copied.
+ */
+impl Clone for CargoString {
+    fn clone(&self) -> Self {
+        Self::from(self.content.clone())
+    }
+}
+
+/*
+ * The empty string, with a length and capacity of 0.
+ */
+impl Default for CargoString {
+    fn default() -> Self {
+        Self::from(String::new())
+    }
+}
+
+/*
+ * Strings are equal if their contents are, whatever their capacities.
+ */
+impl PartialEq for CargoString {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+impl Eq for CargoString {}
+
+impl Hash for CargoString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content.hash(state);
+    }
+}
+
+fn read_cargo_string<R: CargoRead>(r: &mut R) -> Result<CargoString, CargoError> {
+    let mut string = CargoString::new(0, 0, String::new());
+    read_cargo_chars(r, |c| string.append_char(c))?;
+    Ok(string)
+}
+
+/*
+ * Reads a quoted string, passing each character of its content to "push" once any
+ * escape has been decoded.
+ */
+fn read_cargo_chars<R: CargoRead>(r: &mut R, mut push: impl FnMut(char)) -> Result<(), CargoError> {
+    let mut len = 0;
+    let start = r.pos();
+    expect_cargo_char(r, CARGO_QUOTE)?;
+    loop {
+        let at = r.pos();
+        let c = match r.next()? {
+            None => return Err(CargoError::UnexpectedEof { at }),
+            Some(CARGO_QUOTE) => return Ok(()),
+            Some(CARGO_BSLASH) => read_cargo_escape(r, at)?,
+            // Control characters may only appear in a string in escaped form.
+            Some(c) if cargo_is_control(c) => {
+                return Err(CargoError::UnexpectedChar { found: c, at })
+            }
+            Some(c) => c,
+        };
+        len += c.len_utf8();
+        if len > r.config().max_string_len {
+            return Err(CargoError::LimitExceeded { at: start });
+        }
+        push(c);
+    }
+}
+
+/*
+ * Reads the remainder of an escape sequence, the backslash having already been
+ * consumed. A \u escape that denotes a UTF-16 high surrogate must be immediately
+ * followed by a second \u escape for the matching low surrogate, and the pair is
+ * combined into a single character.
+ */
+fn read_cargo_escape<R: CargoRead>(r: &mut R, at: (usize, usize)) -> Result<char, CargoError> {
+    let c = match r.next()? {
+        Some(CARGO_QUOTE) => CARGO_QUOTE,
+        Some(CARGO_BSLASH) => CARGO_BSLASH,
+        Some(CARGO_FSLASH) => CARGO_FSLASH,
+        Some(CARGO_B) => CARGO_BS,
+        Some(CARGO_F) => CARGO_FF,
+        Some(CARGO_N) => CARGO_LF,
+        Some(CARGO_R) => CARGO_CR,
+        Some(CARGO_T) => CARGO_HT,
+        Some(CARGO_U) => {
+            let high = read_cargo_hex4(r, at)?;
+            let code = match high {
+                0xD800..=0xDBFF => {
+                    expect_cargo_char(r, CARGO_BSLASH)
+                        .map_err(|_| CargoError::InvalidEscape { at })?;
+                    expect_cargo_char(r, CARGO_U).map_err(|_| CargoError::InvalidEscape { at })?;
+                    let low = read_cargo_hex4(r, at)?;
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        return Err(CargoError::InvalidEscape { at });
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                }
+                0xDC00..=0xDFFF => return Err(CargoError::InvalidEscape { at }),
+                _ => high,
+            };
+            return char::from_u32(code).ok_or(CargoError::InvalidEscape { at });
+        }
+        None => return Err(CargoError::UnexpectedEof { at: r.pos() }),
+        Some(_) => return Err(CargoError::InvalidEscape { at }),
+    };
+    Ok(c)
+}
+
+fn read_cargo_hex4<R: CargoRead>(r: &mut R, at: (usize, usize)) -> Result<u32, CargoError> {
+    let mut code = 0;
+    for _ in 0..4 {
+        match r.next()? {
+            Some(c) if cargo_is_hex(c) => code = code * 16 + c.to_digit(16).unwrap(),
+            None => return Err(CargoError::UnexpectedEof { at: r.pos() }),
+            Some(_) => return Err(CargoError::InvalidEscape { at }),
+        }
+    }
+    Ok(code)
+}
+
+/*
+ * Structure used to hold a number.
+ * The "text_value" field holds a printable/parseable representation of the number
+ * as Unicode text, conforming to the Argo standard.
+ * The "int_value" field holds the value of the number in integer format, if the
+ * number can be exactly represented as such.
+ * The "float_value" field holds the value of the number in floating-point format.
+ * With the "bigdecimal" feature, the "decimal_value" field holds the exact value of
+ * a number that was read from text or made from an integer, however many digits it
+ * has. It is not used in comparing or hashing numbers.
+ *
+ * If multiple representations of the value of the number are present, they should
+ * agree with each other.
+ * It is up to an application to determine which representation is the appropriate
+ * one to use, based on the semantics of the data being represented.
+ */
+
+#[derive(Debug, Clone)]
+pub struct CargoNumber {
+    string_value: Option<CargoString>,
+    int_value: Option<i64>,
+    float_value: Option<f64>,
+    #[cfg(feature = "bigdecimal")]
+    decimal_value: Option<BigDecimal>,
+}
+
+impl CargoNumber {
+    pub fn as_i64(&self) -> Option<i64> {
+        self.int_value
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        self.float_value
+    }
+    #[cfg(feature = "bigdecimal")]
+    pub fn as_decimal(&self) -> Option<&BigDecimal> {
+        self.decimal_value.as_ref()
+    }
+    /*
+     * Gives a number that has no int_value one if its value is a whole number in
+     * the range of an i64, as in "5.0" or "5e0", with the float_value converted
+     * back from it as for a number read as an integer (so -0.0 becomes 0). The
+     * text of the number is kept for verbatim mode. With the "bigdecimal" feature,
+     * a number whose decimal_value is not whole is left alone, even if its
+     * float_value is.
+     */
+    fn normalize_cargo_number(&mut self) {
+        let Some(f) = self.float_value.filter(|_| self.int_value.is_none()) else {
+            return;
+        };
+        // i64::MIN is a power of two, so it and its negation are exact as floats.
+        if f < i64::MIN as f64 || f >= -(i64::MIN as f64) {
+            return;
+        }
+        // Within that range, the conversion only loses a fraction (or a NaN).
+        let i = f as i64;
+        if i as f64 != f {
+            return;
+        }
+        // The float may have rounded away a fraction, or an integer beyond 2^53.
+        #[cfg(feature = "bigdecimal")]
+        if self
+            .decimal_value
+            .as_ref()
+            .is_some_and(|d| !d.is_integer() || d.to_i64() != Some(i))
+        {
+            return;
+        }
+        self.int_value = Some(i);
+        self.float_value = Some(i as f64);
+    }
+    /*
+     * A number with an int_value is written as an integer. Otherwise it is written
+     * in floating-point form, with the fraction normalized to lie in [0.1, 1.0) and
+     * at most CARGO_PRECISION (or opts.precision) significant digits, followed by
+     * the exponent (if it is nonzero) introduced by a lower-case "e" and without
+     * any "+" sign or leading zeros, so that however the exponent was written in
+     * the input, 1e+05 and 1E5 alike are written as 0.1e6.
+     * Zero is the exception: an integer zero (including "-0", which is the same
+     * integer) is written as "0", and a floating-point zero as "0.0", or as "-0.0"
+     * if it is negative, since IEEE 754 distinguishes the two.
+     * In verbatim mode, a number that has a string_value is written as that text.
+     * With the "bigdecimal" feature, a number that has a decimal_value is written
+     * in the same form but with every one of its significant digits, unless
+     * opts.precision is given.
+     */
+    fn write_cargo_number<W: fmt::Write>(&self, w: &mut W, opts: &WriteOpts) -> fmt::Result {
+        if let Some(text) = self.string_value.as_ref().filter(|_| opts.verbatim_numbers) {
+            return w.write_str(text.as_str());
+        }
+        #[cfg(feature = "bigdecimal")]
+        if let (None, Some(decimal), None) = (self.int_value, &self.decimal_value, opts.precision) {
+            return write_cargo_decimal(w, decimal, self.float_value.unwrap_or_default());
+        }
+        match (self.int_value, self.float_value) {
+            (Some(i), _) if opts.integers_as_floats => write!(w, "{}.0", i),
+            (Some(i), _) => write!(w, "{}", i),
+            (None, Some(f)) => write_cargo_float(w, f, opts.precision),
+            (None, None) => Err(fmt::Error),
+        }
+    }
+}
+
+impl From<i64> for CargoNumber {
+    fn from(i: i64) -> Self {
+        Self {
+            string_value: None,
+            int_value: Some(i),
+            float_value: Some(i as f64),
+            #[cfg(feature = "bigdecimal")]
+            decimal_value: Some(BigDecimal::from(i)),
+        }
+    }
+}
+
+/*
+ * A NaN or infinity has no Cargo form, and could not be written, so it is a
+ * NotFinite error rather than a number.
+ */
+impl TryFrom<f64> for CargoNumber {
+    type Error = CargoError;
+
+    fn try_from(f: f64) -> Result<Self, CargoError> {
+        if !f.is_finite() {
+            return Err(CargoError::NotFinite);
+        }
+        Ok(Self {
+            string_value: None,
+            int_value: None,
+            float_value: Some(f),
+            #[cfg(feature = "bigdecimal")]
+            decimal_value: None,
+        })
+    }
+}
+
+/*
+ * The integer 0, with a float_value of 0.0 to agree with it.
+ */
+impl Default for CargoNumber {
+    fn default() -> Self {
+        Self::from(0)
+    }
+}
+
+/*
+ * Compares the values of two numbers. An int_value is always compared exactly,
+ * even with the float_value of the other number, since rounding it to a float
+ * would make 2^53 + 1 equal to the float 2^53, which is equal to the int 2^53,
+ * which is not equal to 2^53 + 1.
+ */
+fn cmp_cargo_numbers(a: &CargoNumber, b: &CargoNumber) -> Ordering {
+    match (a.int_value, b.int_value, a.float_value, b.float_value) {
+        (Some(a), Some(b), _, _) => a.cmp(&b),
+        (Some(a), None, _, Some(b)) => cmp_cargo_int_float(a, b),
+        (None, Some(b), Some(a), _) => cmp_cargo_int_float(b, a).reverse(),
+        (_, _, Some(a), Some(b)) => cmp_cargo_floats(a, b),
+        (_, _, a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+fn cmp_cargo_int_float(i: i64, f: f64) -> Ordering {
+    // i64::MIN is a power of two, so it and its negation are exact as floats.
+    if f < i64::MIN as f64 {
+        return Ordering::Greater;
+    }
+    if f >= -(i64::MIN as f64) {
+        return Ordering::Less;
+    }
+    // The float lies strictly between t - 1 and t + 1, so only if i is t does the
+    // fraction that the conversion drops decide the order. Either way t is exact
+    // as a float, being below 2^53 or else equal to the float itself.
+    let t = f as i64;
+    i.cmp(&t).then(cmp_cargo_floats(t as f64, f))
+}
+
+/*
+ * A float_value is never a NaN, so any two are ordered, with -0.0 and 0.0 equal.
+ */
+fn cmp_cargo_floats(a: f64, b: f64) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/*
+ * Numbers are compared by value, so that 1 and 1.0 are equal, as described for
+ * cmp_cargo_numbers. The original text is not used.
+ */
+impl PartialEq for CargoNumber {
+    fn eq(&self, other: &Self) -> bool {
+        cmp_cargo_numbers(self, other) == Ordering::Equal
+    }
+}
+
+impl Eq for CargoNumber {}
+
+/*
+ * Numbers are ordered by value in the same way as they are compared for equality,
+ * so that the int 2 comes after the float 1.5 and 1 and 1.0 are neither before the
+ * other. An int_value is compared exactly, and otherwise the float_values are,
+ * with -0.0 and 0.0 equal.
+ */
+impl PartialOrd for CargoNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CargoNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_cargo_numbers(self, other)
+    }
+}
+
+/*
+ * Numbers that are equal must hash alike, and an int_value always has the same
+ * float_value, so numbers are hashed by their float_value alone (with -0.0 taken
+ * as 0.0, since the two are equal).
+ */
+impl Hash for CargoNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.float_value.map(|f| if f == 0.0 { 0.0 } else { f });
+        normalized.map(f64::to_bits).hash(state);
+    }
+}
+
+/*
+ * Writes a decimal exactly, in the same form as write_cargo_float. A zero, which
+ * has no significant digits, is written by write_cargo_float with the sign of the
+ * float_value "f", since a decimal has none.
+ */
+#[cfg(feature = "bigdecimal")]
+fn write_cargo_decimal<W: fmt::Write>(w: &mut W, decimal: &BigDecimal, f: f64) -> fmt::Result {
+    // The value is digits * 10^-scale, which is 0.digits * 10^(len - scale).
+    let (digits, scale) = decimal.as_bigint_and_exponent();
+    let digits = format!("{}", digits);
+    let (negative, digits) = match digits.strip_prefix(CARGO_MINUS) {
+        Some(digits) => (true, digits),
+        None => (false, digits.as_str()),
+    };
+    let significant = digits.trim_end_matches(CARGO_DIGIT0);
+    if significant.is_empty() {
+        return write_cargo_float(w, f, None);
+    }
+    let exp = digits.len() as i64 - scale;
+    if negative {
+        w.write_char(CARGO_MINUS)?;
+    }
+    write!(w, "0.{}", significant)?;
+    if exp != 0 {
+        write!(w, "{}{}", CARGO_E, exp)?;
+    }
+    Ok(())
+}
+
+/*
+ * The exact value of a number with the given text, which is known to be valid.
+ */
+#[cfg(feature = "bigdecimal")]
+fn cargo_decimal_value(text: &str) -> Option<BigDecimal> {
+    BigDecimal::from_str(text).ok()
+}
+
+fn write_cargo_float<W: fmt::Write>(w: &mut W, f: f64, precision: Option<usize>) -> fmt::Result {
+    if f == 0.0 {
+        return w.write_str(if f.is_sign_negative() { "-0.0" } else { "0.0" });
+    }
+    let precision = precision.map_or(CARGO_PRECISION as usize, |p| {
+        p.clamp(1, CARGO_MAX_PRECISION)
+    });
+    // Scientific notation gives the correctly rounded digits as "d.ddde<exp>".
+    let sci = format!("{:.*e}", precision - 1, f.abs());
+    let (mantissa, exp) = sci.split_once(CARGO_E).ok_or(fmt::Error)?;
+    let exp = exp.parse::<i32>().map_err(|_| fmt::Error)? + 1;
+    let digits: String = mantissa.chars().filter(|&c| c != CARGO_PERIOD).collect();
+    if f < 0.0 {
+        w.write_char(CARGO_MINUS)?;
+    }
+    write!(w, "0.{}", digits.trim_end_matches(CARGO_DIGIT0))?;
+    if exp != 0 {
+        write!(w, "{}{}", CARGO_E, exp)?;
+    }
+    Ok(())
+}
+
+/*
+ * Reads a number conforming to the grammar
+ *
+ *     number = [ "-" ] int [ frac ] [ exp ]
+ *     int    = "0" / ( digit1-9 *digit )
+ *     frac   = "." 1*digit
+ *     exp    = ( "e" / "E" ) [ "+" / "-" ] 1*digit
+ *
+ * Numbers without a fraction or exponent that fit in an i64 also get an int_value.
+ * For those, the float_value is the int_value converted, so that "-0" has a
+ * float_value of 0.0 rather than -0.0, while "-0.0" keeps its sign.
+ */
+pub fn read_cargo_number<R: CargoRead>(r: &mut R) -> Result<CargoNumber, CargoError> {
+    let mut text = String::new();
+    let (int_value, float_value) = read_cargo_number_text(r, &mut text)?;
+    Ok(CargoNumber {
+        int_value,
+        float_value: Some(float_value),
+        #[cfg(feature = "bigdecimal")]
+        decimal_value: cargo_decimal_value(&text),
+        string_value: Some(CargoString::from(text)),
+    })
+}
+
+/*
+ * Reads a number as for read_cargo_number onto the end of "text", which is empty
+ * to begin with, and returns its int_value and float_value.
+ */
+fn read_cargo_number_text<R: CargoRead>(
+    r: &mut R,
+    text: &mut String,
+) -> Result<(Option<i64>, f64), CargoError> {
+    let at = r.pos();
+    let mut is_int = true;
+    if r.peek()? == Some(CARGO_MINUS) {
+        text.extend(r.next()?);
+    }
+    match r.peek()? {
+        Some(CARGO_DIGIT0) => text.extend(r.next()?),
+        Some(c) if cargo_is_digit(c) => {
+            read_cargo_digits(r, text, at)?;
+        }
+        _ => return Err(CargoError::InvalidNumber { at }),
+    }
+    if r.peek()? == Some(CARGO_PERIOD) {
+        is_int = false;
+        text.extend(r.next()?);
+        if read_cargo_digits(r, text, at)? == 0 {
+            return Err(CargoError::InvalidNumber { at });
+        }
+    }
+    if r.peek()?.is_some_and(cargo_is_exponent) {
+        is_int = false;
+        text.extend(r.next()?);
+        if let Some(c @ (CARGO_PLUS | CARGO_MINUS)) = r.peek()? {
+            text.push(c);
+            r.next()?;
+        }
+        if read_cargo_digits(r, text, at)? == 0 {
+            return Err(CargoError::InvalidNumber { at });
+        }
+    }
+    if text.len() > r.config().max_number_len {
+        return Err(CargoError::LimitExceeded { at });
+    }
+    let float_value: f64 = text.parse().map_err(|_| CargoError::InvalidNumber { at })?;
+    // An exponent that is too large overflows to infinity, which cannot be represented.
+    if !float_value.is_finite() {
+        return Err(CargoError::InvalidNumber { at });
+    }
+    let int_value = if is_int { cargo_int_value(text) } else { None };
+    Ok((int_value, int_value.map_or(float_value, |i| i as f64)))
+}
+
+/*
+ * The value of an integer made up of an optional minus sign and decimal digits,
+ * or None if it is out of the range of an i64. It is accumulated as a negative
+ * number so that i64::MIN, which has no positive counterpart, can be reached.
+ */
+fn cargo_int_value(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix(CARGO_MINUS) {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let mut value: i64 = 0;
+    for c in digits.chars() {
+        let digit = c.to_digit(10)? as i64;
+        value = value.checked_mul(10)?.checked_sub(digit)?;
+    }
+    if negative {
+        Some(value)
+    } else {
+        value.checked_neg()
+    }
+}
+
+/*
+ * Reads a run of digits onto the end of "text", which holds the number that
+ * starts at "at", and returns how many there were.
+ */
+fn read_cargo_digits<R: CargoRead>(
+    r: &mut R,
+    text: &mut String,
+    at: (usize, usize),
+) -> Result<usize, CargoError> {
+    let mut count = 0;
+    while let Some(c) = r.peek()? {
+        if !cargo_is_digit(c) {
+            break;
+        }
+        if text.len() >= r.config().max_number_len {
+            return Err(CargoError::LimitExceeded { at });
+        }
+        text.push(c);
+        r.next()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/*
+ * Basic Cargo values, represented by the (unquoted) tokens
+ * "true", "false", or "null" in Cargo code.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CargoBasic {
+    CargoNull,
+    CargoTrue(bool),
+    CargoFalse(bool),
+}
+
+impl CargoBasic {
+    fn write_cargo_basic<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        w.write_str(match self {
+            CargoBasic::CargoNull => CARGO_NULL_TOKEN,
+            CargoBasic::CargoTrue(_) => CARGO_TRUE_TOKEN,
+            CargoBasic::CargoFalse(_) => CARGO_FALSE_TOKEN,
+        })
+    }
+}
+fn read_cargo_basic<R: CargoRead>(r: &mut R) -> Result<CargoBasic, CargoError> {
+    let (token, basic) = match r.peek()? {
+        Some(CARGO_T) => (CARGO_TRUE_TOKEN, CargoBasic::CargoTrue(true)),
+        Some(CARGO_F) => (CARGO_FALSE_TOKEN, CargoBasic::CargoFalse(false)),
+        Some(CARGO_N) => (CARGO_NULL_TOKEN, CargoBasic::CargoNull),
+        found => return Err(cargo_unexpected(found, r.pos())),
+    };
+    // A character that does not match is left unread, so that read_cargo_all_errors
+    // can carry on from it.
+    for c in token.chars() {
+        match r.peek()? {
+            Some(found) if found == c => r.next()?,
+            found => return Err(cargo_unexpected(found, r.pos())),
+        };
+    }
+    Ok(basic)
+}
+
+/*
+ * An "array" has an ordered sequence of elements, each of which is just a value.
+ * Here we represent the elements as a Vec, in the same way as for the members of
+ * an object, which takes the place of the circular, doubly linked list with a
+ * sentinel at its head that is used by the C version.
+ *
+ * Note that elements of an array do not have any name, so the "name" field in each
+ * of the elements will be None.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CargoArray {
+    element_list: Vec<CargoValue>,
+}
+
+impl CargoArray {
+    pub fn get(&self, index: usize) -> Option<&CargoValue> {
+        self.element_list.get(index)
+    }
+    pub fn len(&self) -> usize {
+        self.element_list.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.element_list.is_empty()
+    }
+    pub fn iter(&self) -> slice::Iter<'_, CargoValue> {
+        self.element_list.iter()
+    }
+    /*
+     * Adds "value" at the end of the array. If it was taken from an object, it
+     * no longer has the name that it had as a member.
+     */
+    pub fn push(&mut self, mut value: CargoValue) {
+        value.name = None;
+        self.element_list.push(value);
+    }
+    fn write_cargo_array<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOpts,
+        depth: usize,
+        inside: Option<&str>,
+    ) -> fmt::Result {
+        let elements = self.element_list.iter();
+        let brackets = (CARGO_LBRACK, CARGO_RBRACK);
+        write_cargo_container(w, opts, depth, elements, brackets, inside)
+    }
+}
+impl Index<usize> for CargoArray {
+    type Output = CargoValue;
+
+    fn index(&self, index: usize) -> &CargoValue {
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index {} out of bounds for array of length {}",
+                index,
+                self.len()
+            )
+        })
+    }
+}
+
+impl From<Vec<CargoValue>> for CargoArray {
+    fn from(element_list: Vec<CargoValue>) -> Self {
+        Self { element_list }
+    }
+}
+
+/*
+ * Adds each of the values at the end of the array in turn, as push does.
+ */
+impl Extend<CargoValue> for CargoArray {
+    fn extend<I: IntoIterator<Item = CargoValue>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
+
+impl Drop for CargoArray {
+    fn drop(&mut self) {
+        drop_cargo_values(&mut self.element_list);
+    }
+}
+
+impl<'a> IntoIterator for &'a CargoArray {
+    type Item = &'a CargoValue;
+    type IntoIter = slice::Iter<'a, CargoValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/*
+ * Reads an array that is at the given depth of nesting (so 1 if it is not within
+ * any other object or array). The whitespace before its closing bracket is put
+ * in "inside" when it is to be preserved.
+ */
+fn read_cargo_array<R: CargoRead>(
+    r: &mut R,
+    depth: usize,
+    inside: &mut String,
+) -> Result<CargoArray, CargoError> {
+    let mut array = CargoArray {
+        element_list: Vec::new(),
+    };
+    let opened_at = r.pos();
+    expect_cargo_char(r, CARGO_LBRACK)?;
+    let mut before = read_cargo_space(r)?;
+    if r.peek()? == Some(CARGO_RBRACK) {
+        r.next()?;
+        *inside = before;
+        return Ok(array);
+    }
+    loop {
+        expect_cargo_more(r, opened_at)?;
+        let mut element = read_cargo_nested_value(r, depth, before)?;
+        let after = read_cargo_space(r)?;
+        expect_cargo_more(r, opened_at)?;
+        if let Some(space) = element.whitespace.as_deref_mut() {
+            space.after = after;
+        }
+        array.element_list.push(element);
+        let at = r.pos();
+        match r.next()? {
+            Some(CARGO_COMMA) if read_cargo_trailing_close(r, CARGO_RBRACK)? => return Ok(array),
+            Some(CARGO_COMMA) => before = read_cargo_space(r)?,
+            Some(CARGO_RBRACK) => {
+                *inside = take_cargo_space_after(&mut array.element_list);
+                return Ok(array);
+            }
+            found => return Err(cargo_unexpected(found, at)),
+        }
+    }
+}
+
+/*
+ * The whitespace before the closing bracket of a container is recorded as being
+ * inside the container rather than after its last value, so that it stays before
+ * the bracket if more values are added.
+ */
+fn take_cargo_space_after(values: &mut [CargoValue]) -> String {
+    values
+        .last_mut()
+        .and_then(|value| value.whitespace.as_deref_mut())
+        .map(|space| mem::take(&mut space.after))
+        .unwrap_or_default()
+}
+
+/*
+ * An "object" has a list of members, each of which has a name and a value.
+ * To store the members, we use a Vec of CargoValue kept in the order in which
+ * the members were read, with the member name stored in the "name" field of
+ * each CargoValue.
+ *
+ * Note that the collection of members of an object is supposed to be regarded as unordered,
+ * which would permit it to be represented using a hash map or similar data structure,
+ * which we are not doing here.
+ */
+#[derive(Debug, Clone)]
+pub struct CargoObject {
+    member_list: Vec<CargoValue>,
+}
+
+impl CargoObject {
+    pub fn len(&self) -> usize {
+        self.member_list.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.member_list.is_empty()
+    }
+    /*
+     * Returns the member with the given name. If the object contains more than one
+     * member with that name, the last one wins, just as if the members had been
+     * assigned one after another.
+     */
+    pub fn get(&self, name: &str) -> Option<&CargoValue> {
+        self.member_list
+            .iter()
+            .rev()
+            .find(|member| member.name() == Some(name))
+    }
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut CargoValue> {
+        self.member_list
+            .iter_mut()
+            .rev()
+            .find(|member| member.name() == Some(name))
+    }
+    /*
+     * Sets the member with the given name to "value", returning the value it had
+     * before, if any. An existing member is replaced where it stands, so the order
+     * of the members does not change; otherwise the new member goes at the end.
+     * If there are several members with the name, the one replaced is the last,
+     * which is the one that get would return.
+     */
+    pub fn insert(&mut self, name: String, value: CargoValue) -> Option<CargoValue> {
+        if let Some(member) = self.get_mut(&name) {
+            let mut old = mem::replace(member, value);
+            member.name = old.name.take();
+            return Some(old);
+        }
+        let mut member = value;
+        member.name = Some(CargoString::from(name));
+        self.member_list.push(member);
+        None
+    }
+    /*
+     * Removes every member with the given name and returns the value of the last
+     * of them (the one that get would have returned), if there were any.
+     */
+    pub fn remove(&mut self, name: &str) -> Option<CargoValue> {
+        let index = self
+            .member_list
+            .iter()
+            .rposition(|member| member.name() == Some(name))?;
+        let mut removed = self.member_list.remove(index);
+        self.member_list
+            .retain(|member| member.name() != Some(name));
+        removed.name = None;
+        Some(removed)
+    }
+    /*
+     * Iterates over the members in the order in which they appear, as pairs of
+     * name and value.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CargoValue)> {
+        self.member_list
+            .iter()
+            .map(|member| (member.name().unwrap_or_default(), member))
+    }
+    /*
+     * Builds an object from (name, value) pairs like From does, but fails with
+     * DuplicateKey if any name occurs more than once.
+     */
+    pub fn try_from_pairs(pairs: Vec<(String, CargoValue)>) -> Result<Self, CargoError> {
+        let mut names = BTreeSet::new();
+        for (name, _) in &pairs {
+            if !names.insert(name.as_str()) {
+                return Err(CargoError::DuplicateKey(name.clone()));
+            }
+        }
+        Ok(Self::from(pairs))
+    }
+    fn sort_cargo_members(&mut self) {
+        self.member_list.sort_by(|a, b| a.name().cmp(&b.name()));
+    }
+    fn write_cargo_object<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOpts,
+        depth: usize,
+        inside: Option<&str>,
+    ) -> fmt::Result {
+        let braces = (CARGO_LBRACE, CARGO_RBRACE);
+        if opts.sort_keys {
+            let mut members: Vec<&CargoValue> = self.member_list.iter().collect();
+            members.sort_by(|a, b| a.name().cmp(&b.name()));
+            let members = members.into_iter();
+            return write_cargo_container(w, opts, depth, members, braces, inside);
+        }
+        let members = self.member_list.iter();
+        write_cargo_container(w, opts, depth, members, braces, inside)
+    }
+}
+/*
+ * Builds an object from (name, value) pairs, keeping them in the given order.
+ * No check is made for duplicate names; try_from_pairs makes one.
+ */
+impl From<Vec<(String, CargoValue)>> for CargoObject {
+    fn from(members: Vec<(String, CargoValue)>) -> Self {
+        Self {
+            member_list: members
+                .into_iter()
+                .map(|(name, mut value)| {
+                    value.name = Some(CargoString::from(name));
+                    value
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Drop for CargoObject {
+    fn drop(&mut self) {
+        drop_cargo_values(&mut self.member_list);
+    }
+}
+
+/*
+ * Dropping the elements or members of an array or object would by default drop
+ * the values nested in them recursively, so a deeply nested value (which the
+ * iterative parser can read) could exhaust the stack. Instead, the contents of
+ * nested objects and arrays are moved onto a single list, so that each of them
+ * is empty by the time it is dropped.
+ */
+fn drop_cargo_values(values: &mut Vec<CargoValue>) {
+    let mut pending = mem::take(values);
+    while let Some(mut value) = pending.pop() {
+        match &mut value.content {
+            CargoContent::Object(object) => pending.append(&mut object.member_list),
+            CargoContent::Array(array) => pending.append(&mut array.element_list),
+            _ => {}
+        }
+    }
+}
+
+/*
+ * Since the members of an object are unordered, two objects are equal if they
+ * have the same member names, with equal values, in whatever order. As with get,
+ * only the last of several members with the same name counts.
+ */
+impl PartialEq for CargoObject {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: &Self, b: &Self| a.iter().all(|(name, _)| a.get(name) == b.get(name));
+        same(self, other) && same(other, self)
+    }
+}
+
+impl Eq for CargoObject {}
+
+/*
+ * To agree with eq, the members that count (the last with each name) are hashed
+ * in order of their names, so that the order in the object does not matter.
+ */
+impl Hash for CargoObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut members: Vec<&CargoValue> = self.member_list.iter().rev().collect();
+        members.sort_by(|a, b| a.name().cmp(&b.name()));
+        members.dedup_by(|a, b| a.name() == b.name());
+        members.hash(state);
+    }
+}
+
+pub fn read_cargo_object<R: CargoRead>(r: &mut R) -> Result<CargoObject, CargoError> {
+    read_cargo_nested_object(r, 1, &mut String::new())
+}
+
+/*
+ * Reads an object that is at the given depth of nesting, as for read_cargo_array.
+ */
+fn read_cargo_nested_object<R: CargoRead>(
+    r: &mut R,
+    depth: usize,
+    inside: &mut String,
+) -> Result<CargoObject, CargoError> {
+    let mut object = CargoObject {
+        member_list: Vec::new(),
+    };
+    // The names seen so far, kept only when duplicates are to be rejected.
+    let mut names = r.config().reject_duplicate_keys.then(BTreeSet::new);
+    let opened_at = r.pos();
+    expect_cargo_char(r, CARGO_LBRACE)?;
+    let mut before = read_cargo_space(r)?;
+    if r.peek()? == Some(CARGO_RBRACE) {
+        r.next()?;
+        *inside = before;
+        return Ok(object);
+    }
+    loop {
+        expect_cargo_more(r, opened_at)?;
+        let name = read_cargo_string(r)?;
+        if let Some(names) = &mut names {
+            if !names.insert(name.content.clone()) {
+                return Err(CargoError::DuplicateKey(name.content));
+            }
+        }
+        let after_name = read_cargo_space(r)?;
+        expect_cargo_more(r, opened_at)?;
+        expect_cargo_char(r, CARGO_COLON)?;
+        let after_colon = read_cargo_space(r)?;
+        expect_cargo_more(r, opened_at)?;
+        let mut member = read_cargo_nested_value(r, depth, after_colon)?;
+        member.name = Some(name);
+        let after = read_cargo_space(r)?;
+        expect_cargo_more(r, opened_at)?;
+        if let Some(space) = member.whitespace.as_deref_mut() {
+            space.after_colon = mem::replace(&mut space.before, before);
+            space.after_name = after_name;
+            space.after = after;
+        }
+        object.member_list.push(member);
+        let at = r.pos();
+        match r.next()? {
+            Some(CARGO_COMMA) if read_cargo_trailing_close(r, CARGO_RBRACE)? => break,
+            Some(CARGO_COMMA) => before = read_cargo_space(r)?,
+            Some(CARGO_RBRACE) => {
+                *inside = take_cargo_space_after(&mut object.member_list);
+                break;
+            }
+            found => return Err(cargo_unexpected(found, at)),
+        }
+    }
+    if !r.config().preserve_order {
+        object.sort_cargo_members();
+    }
+    Ok(object)
+}
+
+/*
+ * The CargoValue structure is used to represent all kinds of Cargo values.
+ * The "type" field tells what type of value it represents.
+ * It has a "name" field which will hold the name in case it is a member of
+ * an object, and which is None otherwise.  The "content" field is the enum of
+ * the structures that represent the various Cargo types, and its variant always
+ * agrees with the value of the "type" field.
+ */
+#[derive(Debug, Clone)]
+pub struct CargoValue {
+    cargo_type: CargoValueType,
+    name: Option<CargoString>,
+    content: CargoContent,
+    whitespace: Option<Box<CargoWhitespace>>,
+}
+
+/*
+ * The whitespace around a value in the input, recorded when the ParseConfig asks
+ * for it to be preserved. For a member of an object, "before" precedes its name,
+ * and "after_name" and "after_colon" are on either side of the colon. The "after"
+ * field runs up to the following comma (or the end of the input, for the top-level
+ * value). For an object or array, "inside" is the whitespace before its closing
+ * bracket or brace, which is all of the whitespace between them if it is empty.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoWhitespace {
+    pub before: String,
+    pub after_name: String,
+    pub after_colon: String,
+    pub after: String,
+    pub inside: String,
+}
+
+impl CargoValue {
+    fn new(content: CargoContent) -> Self {
+        Self {
+            cargo_type: match &content {
+                CargoContent::Object(_) => CargoValueType::CargoObjectType,
+                CargoContent::Array(_) => CargoValueType::CargoArrayType,
+                CargoContent::String(_) => CargoValueType::CargoStringType,
+                CargoContent::Number(_) => CargoValueType::CargoNumberType,
+                CargoContent::Basic(_) => CargoValueType::CargoBasicType,
+            },
+            name: None,
+            content,
+            whitespace: None,
+        }
+    }
+    pub fn string(s: &str) -> Self {
+        Self::new(CargoContent::String(CargoString::from(String::from(s))))
+    }
+    pub fn number_i64(n: i64) -> Self {
+        Self::new(CargoContent::Number(CargoNumber::from(n)))
+    }
+    /*
+     * A NaN or infinity has no Cargo form, and could not be written, so it gives
+     * None rather than a value.
+     */
+    pub fn number_f64(n: f64) -> Option<Self> {
+        let number = CargoNumber::try_from(n).ok()?;
+        Some(Self::new(CargoContent::Number(number)))
+    }
+    pub fn bool(b: bool) -> Self {
+        Self::new(CargoContent::Basic(if b {
+            CargoBasic::CargoTrue(true)
+        } else {
+            CargoBasic::CargoFalse(false)
+        }))
+    }
+    pub fn null() -> Self {
+        Self::new(CargoContent::Basic(CargoBasic::CargoNull))
+    }
+    pub fn array(elements: Vec<CargoValue>) -> Self {
+        Self::new(CargoContent::Array(Box::new(CargoArray::from(elements))))
+    }
+    /*
+     * Builds an object with the given members, in the given order. As with
+     * CargoObject::from, no check is made for duplicate names.
+     */
+    pub fn object(members: Vec<(String, CargoValue)>) -> Self {
+        Self::new(CargoContent::Object(Box::new(CargoObject::from(members))))
+    }
+    pub fn cargo_type(&self) -> CargoValueType {
+        self.cargo_type
+    }
+    /*
+     * The name of the type of the value as it is usually given for JSON, which
+     * unlike CargoValueType distinguishes booleans from null.
+     */
+    pub fn type_name(&self) -> &'static str {
+        self.content.type_name()
+    }
+    /*
+     * Predicates for each type, as named by type_name, and for scalars (anything
+     * but an object or array) and containers (objects and arrays).
+     */
+    pub fn is_null(&self) -> bool {
+        matches!(self.content, CargoContent::Basic(CargoBasic::CargoNull))
+    }
+    pub fn is_bool(&self) -> bool {
+        matches!(
+            self.content,
+            CargoContent::Basic(CargoBasic::CargoTrue(_) | CargoBasic::CargoFalse(_))
+        )
+    }
+    pub fn is_number(&self) -> bool {
+        matches!(self.content, CargoContent::Number(_))
+    }
+    pub fn is_string(&self) -> bool {
+        matches!(self.content, CargoContent::String(_))
+    }
+    pub fn is_array(&self) -> bool {
+        matches!(self.content, CargoContent::Array(_))
+    }
+    pub fn is_object(&self) -> bool {
+        matches!(self.content, CargoContent::Object(_))
+    }
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+    pub fn is_container(&self) -> bool {
+        self.is_array() || self.is_object()
+    }
+    pub fn content(&self) -> &CargoContent {
+        &self.content
+    }
+    pub fn into_content(self) -> CargoContent {
+        self.content
+    }
+    /*
+     * Moves the content out of the value, leaving null in its place, and returns
+     * it as a value of its own that is not a member of any object. So a part of a
+     * value can be taken for use elsewhere without copying it, as in
+     * object.get_mut("items").map(CargoValue::take). The value keeps its name and
+     * any whitespace that was recorded around it.
+     */
+    pub fn take(&mut self) -> CargoValue {
+        let null = CargoContent::Basic(CargoBasic::CargoNull);
+        let content = mem::replace(&mut self.content, null);
+        self.cargo_type = CargoValueType::CargoBasicType;
+        CargoValue::new(content)
+    }
+    /*
+     * Replaces the content of the value, keeping its name and any whitespace that
+     * was recorded around it.
+     */
+    pub fn set_content(&mut self, content: CargoContent) {
+        let name = self.name.take();
+        let whitespace = self.whitespace.take();
+        *self = Self::new(content);
+        self.name = name;
+        self.whitespace = whitespace;
+    }
+    /*
+     * The whitespace that was recorded around the value when it was read, if any.
+     */
+    pub fn whitespace(&self) -> Option<&CargoWhitespace> {
+        self.whitespace.as_deref()
+    }
+    fn cargo_whitespace(&self, opts: &WriteOpts) -> Option<&CargoWhitespace> {
+        self.whitespace
+            .as_deref()
+            .filter(|_| opts.preserve_whitespace)
+    }
+    /*
+     * A copy of the value that is not a member of any object.
+     */
+    fn unnamed(&self) -> CargoValue {
+        CargoValue::new(self.content.clone())
+    }
+    /*
+     * The object, if this value is one, so that its members can be changed. (There
+     * is no general content_mut, since the content must stay of the same type.)
+     */
+    pub fn as_object_mut(&mut self) -> Option<&mut CargoObject> {
+        match &mut self.content {
+            CargoContent::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+    /*
+     * The name of the value if it is a member of an object, or None otherwise.
+     */
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| name.content.as_str())
+    }
+    /*
+     * Looks up a value nested within this one by a JSON Pointer (RFC 6901) such as
+     * "/foo/0/bar", each segment of which is the name of an object member or the
+     * index of an array element. Within a segment, "~1" stands for "/" and "~0"
+     * for "~". The empty pointer refers to this value itself. If any segment is
+     * missing or does not fit the value it is applied to, the result is None.
+     */
+    pub fn pointer(&self, pointer: &str) -> Option<&CargoValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let mut value = self;
+        for segment in pointer.strip_prefix(CARGO_FSLASH)?.split(CARGO_FSLASH) {
+            let segment = decode_cargo_pointer_segment(segment)?;
+            value = match &value.content {
+                CargoContent::Object(object) => object.get(&segment)?,
+                CargoContent::Array(array) => array.get(cargo_pointer_index(&segment)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+    /*
+     * Calls "f" on this value and then on every value nested within it, depth first
+     * and in the order in which they appear, so that each value is visited before
+     * its elements or members. If "f" replaces the content of a value, it is the
+     * new content that is visited next. The values still to be visited are kept
+     * on a list rather than the stack, so any depth of nesting can be handled.
+     */
+    pub fn visit_mut<F: FnMut(&mut CargoValue)>(&mut self, f: &mut F) {
+        let mut pending = Vec::from([self]);
+        while let Some(value) = pending.pop() {
+            f(value);
+            match &mut value.content {
+                CargoContent::Object(object) => pending.extend(object.member_list.iter_mut().rev()),
+                CargoContent::Array(array) => pending.extend(array.element_list.iter_mut().rev()),
+                _ => {}
+            }
+        }
+    }
+    /*
+     * Rewrites every number in this value that is written with a fraction or an
+     * exponent but is a whole number that fits in an i64, such as 5.0 or 6e0, so
+     * that it has an int_value and is written as an integer (see CargoNumber's
+     * normalize_cargo_number). This is not done when reading, because it changes
+     * which of as_i64 and as_f64 a number answers to.
+     */
+    pub fn normalize_numbers(&mut self) {
+        self.visit_mut(&mut |value| {
+            if let CargoContent::Number(number) = &mut value.content {
+                number.normalize_cargo_number();
+            }
+        });
+    }
+    /*
+     * Iterates over this value and every value nested within it in the same order
+     * as visit_mut, yielding each with its JSON Pointer relative to this value
+     * (so this value itself comes first, with the empty pointer). The pointers are
+     * built as the values are reached, so nothing is done for those not asked for.
+     */
+    pub fn walk(&self) -> impl Iterator<Item = (String, &CargoValue)> {
+        let mut pending = Vec::from([(String::new(), self)]);
+        iter::from_fn(move || {
+            let (path, value) = pending.pop()?;
+            match &value.content {
+                CargoContent::Object(object) => {
+                    pending.extend(object.member_list.iter().rev().map(|member| {
+                        let name = member.name().unwrap_or_default();
+                        (cargo_pointer_path(&path, name), member)
+                    }))
+                }
+                CargoContent::Array(array) => pending.extend(
+                    array
+                        .element_list
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .map(|(i, element)| (format!("{}/{}", path, i), element)),
+                ),
+                _ => {}
+            }
+            Some((path, value))
+        })
+    }
+    /*
+     * Applies "patch" to this value as a JSON Merge Patch (RFC 7386). If the patch
+     * is an object, each of its members is merged into the member of this value
+     * with the same name, except that a member whose value is null removes that
+     * member instead (and does nothing if there is none). If this value is not an
+     * object it is first replaced by an empty one. Any other patch replaces this
+     * value as a whole. As elsewhere, only the last of several members of the
+     * patch with the same name counts.
+     */
+    pub fn merge_patch(&mut self, patch: &CargoValue) {
+        let CargoContent::Object(patch) = &patch.content else {
+            self.set_content(patch.content.clone());
+            return;
+        };
+        if !matches!(self.content, CargoContent::Object(_)) {
+            self.set_content(CargoContent::Object(Box::new(
+                CargoObject::from(Vec::new()),
+            )));
+        }
+        let CargoContent::Object(target) = &mut self.content else {
+            unreachable!();
+        };
+        let last = cargo_members_by_name(patch);
+        for (name, member) in patch.iter() {
+            if !core::ptr::eq(last[name], member) {
+                continue;
+            }
+            if member.content == CargoContent::Basic(CargoBasic::CargoNull) {
+                target.remove(name);
+            } else if let Some(existing) = target.get_mut(name) {
+                existing.merge_patch(member);
+            } else {
+                let mut value = CargoValue::null();
+                value.merge_patch(member);
+                target.insert(String::from(name), value);
+            }
+        }
+    }
+    /*
+     * Writes the value to w in compact canonical form. The compact form is also
+     * available as a String through to_string, since CargoValue implements Display.
+     * The output is buffered, and w is flushed once all of it has been written.
+     */
+    #[cfg(feature = "std")]
+    pub fn to_writer<W: io::Write>(&self, w: &mut W) -> Result<(), CargoError> {
+        self.to_writer_with_opts(w, &WriteOpts::default())
+    }
+    /*
+     * Writes the value to w pretty-printed with "indent" spaces per level, followed
+     * by a newline, just as -c -p does.
+     */
+    #[cfg(feature = "std")]
+    pub fn to_writer_pretty<W: io::Write>(&self, w: &mut W, indent: u32) -> Result<(), CargoError> {
+        self.to_writer_with_opts(w, &WriteOpts::pretty(indent))
+    }
+    pub fn to_string_pretty(&self, indent: u32) -> String {
+        let mut out = String::new();
+        write_cargo_value(&mut out, self, &WriteOpts::pretty(indent))
+            .expect("a value read or built through the public API can always be written");
+        out
+    }
+    /*
+     * A 64-bit FNV-1a hash of the compact canonical form of the value, as given by
+     * to_string, so values read from differently formatted input hash alike if
+     * they canonicalize alike. Unlike the Hash implementation, the result depends
+     * on nothing but those bytes, so it is the same from one run (or build) to the
+     * next and can be stored. Since the canonical form keeps the members of an
+     * object in order, objects that are equal but in a different order do not, in
+     * general, have the same canonical_hash.
+     */
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = CargoFnvHasher(CARGO_FNV_OFFSET);
+        write_cargo_value(&mut hasher, self, &WriteOpts::default())
+            .expect("a value read or built through the public API can always be written");
+        hasher.0
+    }
+    /*
+     * Writes the value to w as to_writer does, but formatted according to opts.
+     */
+    #[cfg(feature = "std")]
+    pub fn to_writer_with_opts<W: io::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOpts,
+    ) -> Result<(), CargoError> {
+        let mut out = CargoIoWriter::new(w, opts);
+        let result = write_cargo_value(&mut out, self, opts).map_err(|_| out.take_error());
+        out.finish(result)
+    }
+    /*
+     * Writes the value in canonical form, preceded by its name and a colon if it
+     * is a member of an object, as it would appear within that object.
+     */
+    pub fn write<W: fmt::Write>(&self, w: &mut W, opts: &WriteOpts) -> fmt::Result {
+        self.write_cargo_member(w, opts, 0)
+    }
+    fn write_cargo_member<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOpts,
+        depth: usize,
+    ) -> fmt::Result {
+        let space = self.cargo_whitespace(opts);
+        if let Some(name) = &self.name {
+            write_cargo_colored(w, opts, CARGO_COLOR_NAME, |w| {
+                name.write_cargo_string(w, opts)
+            })?;
+            match space {
+                Some(space) => {
+                    w.write_str(&space.after_name)?;
+                    w.write_char(CARGO_COLON)?;
+                    w.write_str(&space.after_colon)?;
+                }
+                None => {
+                    w.write_char(CARGO_COLON)?;
+                    if opts.indent.is_some() {
+                        w.write_char(CARGO_SPACE)?;
+                    }
+                }
+            }
+        }
+        self.write_cargo_content(w, opts, depth)
+    }
+    fn write_cargo_content<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOpts,
+        depth: usize,
+    ) -> fmt::Result {
+        let inside = self
+            .cargo_whitespace(opts)
+            .map(|space| space.inside.as_str());
+        match &self.content {
+            CargoContent::Object(object) => object.write_cargo_object(w, opts, depth, inside),
+            CargoContent::Array(array) => array.write_cargo_array(w, opts, depth, inside),
+            content => content.write_cargo(w, opts, depth),
+        }
+    }
+}
+
+/*
+ * Decodes a segment of a JSON Pointer, in which "~1" stands for "/" and "~0" for
+ * "~". A "~" followed by anything else makes the pointer invalid.
+ */
+fn decode_cargo_pointer_segment(segment: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        decoded.push(match c {
+            CARGO_TILDE => match chars.next()? {
+                CARGO_DIGIT0 => CARGO_TILDE,
+                c if c == AsciiChar::_1.as_char() => CARGO_FSLASH,
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(decoded)
+}
+
+/*
+ * An array index in a pointer is a decimal number without leading zeros. (The
+ * "-" that RFC 6901 allows refers to the element after the last, which never
+ * exists, so it needs no special case here.)
+ */
+fn cargo_pointer_index(segment: &str) -> Option<usize> {
+    let digits = !segment.is_empty() && segment.chars().all(cargo_is_digit);
+    if !digits || (segment.len() > 1 && segment.starts_with(CARGO_DIGIT0)) {
+        return None;
+    }
+    segment.parse().ok()
+}
+
+/*
+ * Values are equal if they have the same name (if any) and equal contents.
+ */
+impl PartialEq for CargoValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.content == other.content
+    }
+}
+
+impl Eq for CargoValue {}
+
+impl Hash for CargoValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+        self.content.hash(state);
+    }
+}
+
+impl From<CargoContent> for CargoValue {
+    fn from(content: CargoContent) -> Self {
+        Self::new(content)
+    }
+}
+
+/*
+ * A single difference found by diff_cargo_values, at the place in the documents
+ * given by "path" as a JSON Pointer (so "" for the top-level value). The values
+ * recorded in "kind" are copies, without any member name.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CargoDiff {
+    pub path: String,
+    pub kind: CargoDiffKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CargoDiffKind {
+    Added(CargoValue),
+    Removed(CargoValue),
+    Changed { old: CargoValue, new: CargoValue },
+}
+
+/*
+ * Finds the differences between "a" and "b". Where both are objects their
+ * members are matched by name, in whatever order they appear (and, as with get,
+ * only the last of several members with the same name counts), and where both
+ * are arrays their elements are matched by index. Anything else that is not
+ * equal is Changed as a whole. The differences are listed in the order in which
+ * they occur in "a", followed by anything added in "b" at the same level. As in
+ * visit_mut, the values still to be compared are kept on a list rather than the
+ * stack.
+ */
+pub fn diff_cargo_values(a: &CargoValue, b: &CargoValue) -> Vec<CargoDiff> {
+    enum Step<'a> {
+        Compare(String, &'a CargoValue, &'a CargoValue),
+        Report(Box<CargoDiff>),
+    }
+    let report = |path: &str, kind| {
+        Step::Report(Box::new(CargoDiff {
+            path: String::from(path),
+            kind,
+        }))
+    };
+    let mut diffs = Vec::new();
+    let mut pending = Vec::from([Step::Compare(String::new(), a, b)]);
+    while let Some(step) = pending.pop() {
+        let (path, a, b) = match step {
+            Step::Compare(path, a, b) => (path, a, b),
+            Step::Report(diff) => {
+                diffs.push(*diff);
+                continue;
+            }
+        };
+        let mut steps = Vec::new();
+        match (&a.content, &b.content) {
+            (CargoContent::Object(old), CargoContent::Object(new)) => {
+                let old_members = cargo_members_by_name(old);
+                let new_members = cargo_members_by_name(new);
+                for (name, member) in old.iter() {
+                    if !core::ptr::eq(old_members[name], member) {
+                        continue;
+                    }
+                    let member_path = cargo_pointer_path(&path, name);
+                    steps.push(match new_members.get(name) {
+                        Some(&other) => Step::Compare(member_path, member, other),
+                        None => report(&member_path, CargoDiffKind::Removed(member.unnamed())),
+                    });
+                }
+                for (name, member) in new.iter() {
+                    if core::ptr::eq(new_members[name], member) && !old_members.contains_key(name) {
+                        let member_path = cargo_pointer_path(&path, name);
+                        steps.push(report(&member_path, CargoDiffKind::Added(member.unnamed())));
+                    }
+                }
+            }
+            (CargoContent::Array(old), CargoContent::Array(new)) => {
+                for i in 0..old.len().max(new.len()) {
+                    let element_path = cargo_pointer_path(&path, &format!("{}", i));
+                    steps.push(match (old.get(i), new.get(i)) {
+                        (Some(x), Some(y)) => Step::Compare(element_path, x, y),
+                        (Some(x), None) => {
+                            report(&element_path, CargoDiffKind::Removed(x.unnamed()))
+                        }
+                        (_, y) => report(&element_path, CargoDiffKind::Added(y.unwrap().unnamed())),
+                    });
+                }
+            }
+            (old, new) if old != new => steps.push(report(
+                &path,
+                CargoDiffKind::Changed {
+                    old: a.unnamed(),
+                    new: b.unnamed(),
+                },
+            )),
+            _ => {}
+        }
+        pending.extend(steps.into_iter().rev());
+    }
+    diffs
+}
+
+/*
+ * Maps each member name of an object to its last member with that name.
+ */
+fn cargo_members_by_name(object: &CargoObject) -> BTreeMap<&str, &CargoValue> {
+    object.iter().collect()
+}
+
+/*
+ * The offset basis and prime of the 64-bit FNV-1a hash, as used by canonical_hash.
+ */
+const CARGO_FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const CARGO_FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/*
+ * Hashes the bytes of the UTF-8 text written to it, so that the canonical form of
+ * a value can be hashed without being collected into a String first.
+ */
+struct CargoFnvHasher(u64);
+
+impl fmt::Write for CargoFnvHasher {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(CARGO_FNV_PRIME);
+        }
+        Ok(())
+    }
+}
+
+/*
+ * Appends a segment to a JSON Pointer, escaping "~" and "/" within it.
+ */
+fn cargo_pointer_path(path: &str, segment: &str) -> String {
+    let mut out = String::from(path);
+    out.push(CARGO_FSLASH);
+    for c in segment.chars() {
+        match c {
+            CARGO_TILDE => out.push_str("~0"),
+            CARGO_FSLASH => out.push_str("~1"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/*
+ * Counts of the values of each type in a document, including the top-level value
+ * and every element and member at any depth. The "max_depth" field is the
+ * greatest number of objects and arrays that enclose one another, so it is 0 for
+ * a document that is just a single string, number or basic value.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    pub values: usize,
+    pub max_depth: usize,
+}
+
+impl CargoStats {
+    /*
+     * As in visit_mut, the values still to be counted (each with its depth) are
+     * kept on a list rather than the stack, so any depth of nesting can be handled.
+     */
+    pub fn of(value: &CargoValue) -> Self {
+        let mut stats = Self::default();
+        let mut pending = Vec::from([(value, 0)]);
+        while let Some((value, depth)) = pending.pop() {
+            stats.values += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            match &value.content {
+                CargoContent::Object(object) => {
+                    stats.objects += 1;
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    pending.extend(object.member_list.iter().map(|member| (member, depth + 1)));
+                }
+                CargoContent::Array(array) => {
+                    stats.arrays += 1;
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    pending.extend(
+                        array
+                            .element_list
+                            .iter()
+                            .map(|element| (element, depth + 1)),
+                    );
+                }
+                CargoContent::String(_) => stats.strings += 1,
+                CargoContent::Number(_) => stats.numbers += 1,
+                CargoContent::Basic(CargoBasic::CargoNull) => stats.nulls += 1,
+                CargoContent::Basic(_) => stats.booleans += 1,
+            }
+        }
+        stats
+    }
+}
+
+/*
+ * The statistics are displayed one per line, as "name: count".
+ */
+impl fmt::Display for CargoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "objects: {}", self.objects)?;
+        writeln!(f, "arrays: {}", self.arrays)?;
+        writeln!(f, "strings: {}", self.strings)?;
+        writeln!(f, "numbers: {}", self.numbers)?;
+        writeln!(f, "booleans: {}", self.booleans)?;
+        writeln!(f, "nulls: {}", self.nulls)?;
+        writeln!(f, "values: {}", self.values)?;
+        write!(f, "max depth: {}", self.max_depth)
+    }
+}
+
+/*
+ * The size of the text that would be written for a value with the given opts,
+ * found by writing it to a sink that only measures it. The "lines" field counts
+ * the lines of the text (with a final newline ending the last line rather than
+ * starting another), and "max_width" is the number of characters on the longest
+ * line, not counting the line ending. A tab counts as reaching the next multiple
+ * of "tab_width" columns, as it would be displayed.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoLayout {
+    pub lines: usize,
+    pub max_width: usize,
+}
+
+impl CargoLayout {
+    pub fn of(value: &CargoValue, opts: &WriteOpts, tab_width: usize) -> Self {
+        let mut sink = CargoLayoutSink {
+            layout: Self::default(),
+            width: 0,
+            tab_width: tab_width.max(1),
+        };
+        write_cargo_value(&mut sink, value, opts)
+            .expect("a value read or built through the public API can always be written");
+        if sink.width > 0 {
+            sink.layout.lines += 1;
+        }
+        sink.layout
+    }
+}
+
+impl fmt::Display for CargoLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "lines: {}", self.lines)?;
+        write!(f, "max width: {}", self.max_width)
+    }
+}
+
+/*
+ * Keeps track of the width of the line being written so far, as CargoLayout::of
+ * writes a value to it.
+ */
+struct CargoLayoutSink {
+    layout: CargoLayout,
+    width: usize,
+    tab_width: usize,
+}
+
+impl fmt::Write for CargoLayoutSink {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                CARGO_LF => {
+                    self.layout.lines += 1;
+                    self.width = 0;
+                }
+                CARGO_CR => {}
+                CARGO_HT => self.width += self.tab_width - self.width % self.tab_width,
+                _ => self.width += 1,
+            }
+            self.layout.max_width = self.layout.max_width.max(self.width);
+        }
+        Ok(())
+    }
+}
+
+/*
+ * Conversions to Rust values, which fail with a TypeMismatch if the value is not
+ * of the right type. Only a number that was written as an integer (and fits in an
+ * i64) converts to an i64, while any number converts to an f64.
+ */
+impl TryFrom<&CargoValue> for i64 {
+    type Error = CargoError;
+
+    fn try_from(value: &CargoValue) -> Result<Self, Self::Error> {
+        value
+            .content
+            .as_i64()
+            .ok_or_else(|| cargo_type_mismatch("integer", value))
+    }
+}
+
+impl TryFrom<&CargoValue> for f64 {
+    type Error = CargoError;
+
+    fn try_from(value: &CargoValue) -> Result<Self, Self::Error> {
+        value
+            .content
+            .as_f64()
+            .ok_or_else(|| cargo_type_mismatch("number", value))
+    }
+}
+
+impl TryFrom<&CargoValue> for bool {
+    type Error = CargoError;
+
+    fn try_from(value: &CargoValue) -> Result<Self, Self::Error> {
+        value
+            .content
+            .as_bool()
+            .ok_or_else(|| cargo_type_mismatch("boolean", value))
+    }
+}
+
+impl TryFrom<&CargoValue> for String {
+    type Error = CargoError;
+
+    fn try_from(value: &CargoValue) -> Result<Self, Self::Error> {
+        value
+            .content
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| cargo_type_mismatch("string", value))
+    }
+}
+
+fn cargo_type_mismatch(expected: &'static str, value: &CargoValue) -> CargoError {
+    cargo_content_mismatch(expected, &value.content)
+}
+
+fn cargo_content_mismatch(expected: &'static str, content: &CargoContent) -> CargoError {
+    CargoError::TypeMismatch {
+        expected,
+        found: content.type_name(),
+    }
+}
+
+impl FromStr for CargoValue {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parse_str(s)
+    }
+}
+
+impl fmt::Display for CargoValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.content.fmt(f)
+    }
+}
+
+/*
+ * Writes a top-level value, followed by a single newline if opts call for one
+ * (by default, only when pretty-printing).
+ */
+pub fn write_cargo_value<W: fmt::Write>(
+    w: &mut W,
+    value: &CargoValue,
+    opts: &WriteOpts,
+) -> fmt::Result {
+    let space = value.cargo_whitespace(opts);
+    if let Some(space) = space {
+        w.write_str(&space.before)?;
+    }
+    value.write_cargo_content(w, opts, 0)?;
+    if let Some(space) = space {
+        w.write_str(&space.after)?;
+    }
+    if opts.final_newline() {
+        opts.line_ending.write_cargo_line_ending(w)?;
+    }
+    Ok(())
+}
+
+/*
+ * Reads a single value of any type, skipping any whitespace that precedes it.
+ * The type of value is determined by its first character.
+ */
+pub fn read_cargo_value<R: CargoRead>(r: &mut R) -> Result<CargoValue, CargoError> {
+    if r.config().iterative {
+        return read_cargo_value_iterative(r);
+    }
+    let before = read_cargo_space(r)?;
+    let mut value = read_cargo_nested_value(r, 0, before)?;
+    if let Some(space) = value.whitespace.as_deref_mut() {
+        space.after = read_cargo_space(r)?;
+    }
+    Ok(value)
+}
+
+/*
+ * Reads a top-level array, calling "f" on each of its elements in turn as soon as
+ * it has been read, and dropping it before reading the next, so that only one
+ * element is held in memory at a time. Anything other than an array is rejected
+ * with an UnexpectedChar error at its first character. The elements are read
+ * with the recursive parser, as the "iterative" option is not used.
+ */
+pub fn read_cargo_elements<R: CargoRead, F: FnMut(&CargoValue) -> Result<(), CargoError>>(
+    r: &mut R,
+    f: &mut F,
+) -> Result<(), CargoError> {
+    skip_cargo_whitespace(r)?;
+    let opened_at = r.pos();
+    check_cargo_depth(r, 1)?;
+    expect_cargo_char(r, CARGO_LBRACK)?;
+    skip_cargo_whitespace(r)?;
+    if r.peek()? == Some(CARGO_RBRACK) {
+        r.next()?;
+        return Ok(());
+    }
+    loop {
+        expect_cargo_more(r, opened_at)?;
+        f(&read_cargo_nested_value(r, 1, String::new())?)?;
+        skip_cargo_whitespace(r)?;
+        expect_cargo_more(r, opened_at)?;
+        let at = r.pos();
+        match r.next()? {
+            Some(CARGO_COMMA) if read_cargo_trailing_close(r, CARGO_RBRACK)? => return Ok(()),
+            Some(CARGO_COMMA) => skip_cargo_whitespace(r)?,
+            Some(CARGO_RBRACK) => return Ok(()),
+            found => return Err(cargo_unexpected(found, at)),
+        }
+    }
+}
+
+/*
+ * Reads a value with the recursive parser, where "depth" is the number of objects
+ * and arrays that enclose it. Any whitespace before the value has already been
+ * read, and is given as "before" if it is to be preserved.
+ */
+fn read_cargo_nested_value<R: CargoRead>(
+    r: &mut R,
+    depth: usize,
+    before: String,
+) -> Result<CargoValue, CargoError> {
+    let mut inside = String::new();
+    let content = match r.peek()? {
+        Some(CARGO_LBRACE) => {
+            check_cargo_depth(r, depth + 1)?;
+            let object = read_cargo_nested_object(r, depth + 1, &mut inside)?;
+            CargoContent::Object(Box::new(object))
+        }
+        Some(CARGO_LBRACK) => {
+            check_cargo_depth(r, depth + 1)?;
+            CargoContent::Array(Box::new(read_cargo_array(r, depth + 1, &mut inside)?))
+        }
+        _ => read_cargo_scalar(r)?,
+    };
+    let mut value = CargoValue::new(content);
+    if r.config().preserve_whitespace {
+        value.whitespace = Some(Box::new(CargoWhitespace {
+            before,
+            inside,
+            ..CargoWhitespace::default()
+        }));
+    }
+    Ok(value)
+}
+
+/*
+ * Reads a single value in the same way as read_cargo_value, but by building it
+ * from the events reported by read_cargo_events. The objects and arrays that are
+ * still open are kept on a stack, each with the name it will have as a member
+ * and, if duplicates are to be rejected, the names of its own members so far.
+ */
+fn read_cargo_value_iterative<R: CargoRead>(r: &mut R) -> Result<CargoValue, CargoError> {
+    let mut open: Vec<(Option<CargoString>, CargoContent, Option<BTreeSet<String>>)> = Vec::new();
+    let mut name: Option<CargoString> = None;
+    let mut root = None;
+    let reject_duplicate_keys = r.config().reject_duplicate_keys;
+    let preserve_order = r.config().preserve_order;
+    read_cargo_events(r, &mut |event| {
+        let content = match event {
+            CargoEvent::StartObject => {
+                let object = CargoObject {
+                    member_list: Vec::new(),
+                };
+                let names = reject_duplicate_keys.then(BTreeSet::new);
+                open.push((name.take(), CargoContent::Object(Box::new(object)), names));
+                return Ok(());
+            }
+            CargoEvent::StartArray => {
+                let array = CargoArray {
+                    element_list: Vec::new(),
+                };
+                open.push((name.take(), CargoContent::Array(Box::new(array)), None));
+                return Ok(());
+            }
+            CargoEvent::Key(key) => {
+                if let Some((_, _, Some(names))) = open.last_mut() {
+                    if !names.insert(key.clone()) {
+                        return Err(CargoError::DuplicateKey(key));
+                    }
+                }
+                name = Some(CargoString::from(key));
+                return Ok(());
+            }
+            CargoEvent::EndObject | CargoEvent::EndArray => {
+                let (container_name, mut content, _) = open.pop().unwrap();
+                if let (CargoContent::Object(object), false) = (&mut content, preserve_order) {
+                    object.sort_cargo_members();
+                }
+                name = container_name;
+                content
+            }
+            CargoEvent::Str(string) => CargoContent::String(CargoString::from(string)),
+            CargoEvent::Number(number) => CargoContent::Number(number),
+            CargoEvent::Bool(true) => CargoContent::Basic(CargoBasic::CargoTrue(true)),
+            CargoEvent::Bool(false) => CargoContent::Basic(CargoBasic::CargoFalse(false)),
+            CargoEvent::Null => CargoContent::Basic(CargoBasic::CargoNull),
+        };
+        let mut value = CargoValue::new(content);
+        value.name = name.take();
+        match open.last_mut() {
+            Some((_, CargoContent::Object(object), _)) => object.member_list.push(value),
+            Some((_, CargoContent::Array(array), _)) => array.element_list.push(value),
+            Some(_) => unreachable!(),
+            None => root = Some(value),
+        }
+        Ok(())
+    })?;
+    Ok(root.unwrap())
+}
+
+/*
+ * Reads a value that is not an object or an array, the type of which is again
+ * determined by its first character. Something that looks like an attempt at a
+ * number, such as "+1" or "NaN", is reported as an invalid number rather than an
+ * unexpected character.
+ */
+fn read_cargo_scalar<R: CargoRead>(r: &mut R) -> Result<CargoContent, CargoError> {
+    Ok(match r.peek()? {
+        Some(CARGO_QUOTE) => CargoContent::String(read_cargo_string(r)?),
+        Some(c) if c == CARGO_MINUS || cargo_is_digit(c) => {
+            CargoContent::Number(read_cargo_number(r)?)
+        }
+        Some(CARGO_T | CARGO_F | CARGO_N) => CargoContent::Basic(read_cargo_basic(r)?),
+        found => return Err(cargo_not_a_value(found, r.pos())),
+    })
+}
+
+/*
+ * The error for a value that starts with "found", which cannot start one.
+ */
+fn cargo_not_a_value(found: Option<char>, at: (usize, usize)) -> CargoError {
+    match found {
+        Some(c)
+            if c == CARGO_PLUS
+                || CARGO_NAN_TOKEN.starts_with(c)
+                || CARGO_INFINITY_TOKEN.starts_with(c) =>
+        {
+            CargoError::InvalidNumber { at }
+        }
+        found => cargo_unexpected(found, at),
+    }
+}
+
+/*
+ * A value read by read_cargo_value_in_arena, which borrows its strings and the
+ * elements and members of its arrays and objects from the arena that it was read
+ * into, so that it is freed all at once with the arena. A number is given as its
+ * text in the input, from which as_i64 and as_f64 get the int_value and the
+ * float_value that a CargoNumber would have. Each member is a (name, value) pair.
+ */
+#[cfg(feature = "bumpalo")]
+#[derive(Debug, Clone, Copy)]
+pub enum CargoValueRef<'a> {
+    Object(&'a [(&'a str, CargoValueRef<'a>)]),
+    Array(&'a [CargoValueRef<'a>]),
+    String(&'a str),
+    Number(&'a str),
+    Basic(CargoBasic),
+}
+
+#[cfg(feature = "bumpalo")]
+impl CargoValueRef<'_> {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            CargoValueRef::Number(text) => cargo_int_value(text),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CargoValueRef::Number(text) => match cargo_int_value(text) {
+                Some(i) => Some(i as f64),
+                None => text.parse().ok(),
+            },
+            _ => None,
+        }
+    }
+    /*
+     * Copies the value out of the arena, giving the CargoValue that
+     * read_cargo_value would have read from the same input.
+     */
+    pub fn to_value(&self) -> CargoValue {
+        let content = match *self {
+            CargoValueRef::Object(members) => {
+                let members: Vec<(String, CargoValue)> = members
+                    .iter()
+                    .map(|(name, value)| (String::from(*name), value.to_value()))
+                    .collect();
+                CargoContent::Object(Box::new(CargoObject::from(members)))
+            }
+            CargoValueRef::Array(elements) => CargoContent::Array(Box::new(CargoArray {
+                element_list: elements.iter().map(Self::to_value).collect(),
+            })),
+            CargoValueRef::String(s) => CargoContent::String(CargoString::from(String::from(s))),
+            CargoValueRef::Number(text) => CargoContent::Number(CargoNumber {
+                string_value: Some(CargoString::from(String::from(text))),
+                int_value: self.as_i64(),
+                float_value: self.as_f64(),
+                #[cfg(feature = "bigdecimal")]
+                decimal_value: cargo_decimal_value(text),
+            }),
+            CargoValueRef::Basic(basic) => CargoContent::Basic(basic),
+        };
+        CargoValue::new(content)
+    }
+}
+
+/*
+ * Reads a single value in the same way as read_cargo_value, but into "arena".
+ * Each string and number is read into a buffer that is reused for all of them and
+ * then copied into the arena, and the elements or members of each array or object
+ * are collected in a vector that is itself in the arena, so that nothing else is
+ * allocated unless duplicate names are rejected. The "iterative" and
+ * "preserve_whitespace" options are not used.
+ */
+#[cfg(feature = "bumpalo")]
+pub fn read_cargo_value_in_arena<'a, R: CargoRead>(
+    r: &mut R,
+    arena: &'a Bump,
+) -> Result<CargoValueRef<'a>, CargoError> {
+    skip_cargo_whitespace(r)?;
+    let value = read_cargo_nested_value_in_arena(r, arena, 0, &mut String::new())?;
+    skip_cargo_whitespace(r)?;
+    Ok(value)
+}
+
+/*
+ * Reads a value into the arena at the given depth, as for read_cargo_nested_value,
+ * using "buf" for the text of any string or number.
+ */
+#[cfg(feature = "bumpalo")]
+fn read_cargo_nested_value_in_arena<'a, R: CargoRead>(
+    r: &mut R,
+    arena: &'a Bump,
+    depth: usize,
+    buf: &mut String,
+) -> Result<CargoValueRef<'a>, CargoError> {
+    match r.peek()? {
+        Some(CARGO_LBRACE) => {
+            check_cargo_depth(r, depth + 1)?;
+            read_cargo_object_in_arena(r, arena, depth + 1, buf)
+        }
+        Some(CARGO_LBRACK) => {
+            check_cargo_depth(r, depth + 1)?;
+            read_cargo_array_in_arena(r, arena, depth + 1, buf)
+        }
+        Some(CARGO_QUOTE) => Ok(CargoValueRef::String(read_cargo_str_in_arena(
+            r, arena, buf,
+        )?)),
+        Some(c) if c == CARGO_MINUS || cargo_is_digit(c) => {
+            buf.clear();
+            read_cargo_number_text(r, buf)?;
+            Ok(CargoValueRef::Number(arena.alloc_str(buf)))
+        }
+        Some(CARGO_T | CARGO_F | CARGO_N) => Ok(CargoValueRef::Basic(read_cargo_basic(r)?)),
+        found => Err(cargo_not_a_value(found, r.pos())),
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+fn read_cargo_str_in_arena<'a, R: CargoRead>(
+    r: &mut R,
+    arena: &'a Bump,
+    buf: &mut String,
+) -> Result<&'a str, CargoError> {
+    buf.clear();
+    read_cargo_chars(r, |c| buf.push(c))?;
+    Ok(arena.alloc_str(buf))
+}
+
+#[cfg(feature = "bumpalo")]
+fn read_cargo_array_in_arena<'a, R: CargoRead>(
+    r: &mut R,
+    arena: &'a Bump,
+    depth: usize,
+    buf: &mut String,
+) -> Result<CargoValueRef<'a>, CargoError> {
+    let mut elements = bumpalo::collections::Vec::new_in(arena);
+    let opened_at = r.pos();
+    expect_cargo_char(r, CARGO_LBRACK)?;
+    skip_cargo_whitespace(r)?;
+    if r.peek()? == Some(CARGO_RBRACK) {
+        r.next()?;
+        return Ok(CargoValueRef::Array(elements.into_bump_slice()));
+    }
+    loop {
+        expect_cargo_more(r, opened_at)?;
+        elements.push(read_cargo_nested_value_in_arena(r, arena, depth, buf)?);
+        skip_cargo_whitespace(r)?;
+        expect_cargo_more(r, opened_at)?;
+        let at = r.pos();
+        match r.next()? {
+            Some(CARGO_COMMA) if read_cargo_trailing_close(r, CARGO_RBRACK)? => break,
+            Some(CARGO_COMMA) => skip_cargo_whitespace(r)?,
+            Some(CARGO_RBRACK) => break,
+            found => return Err(cargo_unexpected(found, at)),
+        }
+    }
+    Ok(CargoValueRef::Array(elements.into_bump_slice()))
+}
+
+#[cfg(feature = "bumpalo")]
+fn read_cargo_object_in_arena<'a, R: CargoRead>(
+    r: &mut R,
+    arena: &'a Bump,
+    depth: usize,
+    buf: &mut String,
+) -> Result<CargoValueRef<'a>, CargoError> {
+    let mut members = bumpalo::collections::Vec::new_in(arena);
+    let mut names = r.config().reject_duplicate_keys.then(BTreeSet::new);
+    let opened_at = r.pos();
+    expect_cargo_char(r, CARGO_LBRACE)?;
+    skip_cargo_whitespace(r)?;
+    if r.peek()? == Some(CARGO_RBRACE) {
+        r.next()?;
+        return Ok(CargoValueRef::Object(members.into_bump_slice()));
+    }
+    loop {
+        expect_cargo_more(r, opened_at)?;
+        let name = read_cargo_str_in_arena(r, arena, buf)?;
+        if let Some(names) = &mut names {
+            if !names.insert(name) {
+                return Err(CargoError::DuplicateKey(String::from(name)));
+            }
+        }
+        skip_cargo_whitespace(r)?;
+        expect_cargo_more(r, opened_at)?;
+        expect_cargo_char(r, CARGO_COLON)?;
+        skip_cargo_whitespace(r)?;
+        expect_cargo_more(r, opened_at)?;
+        members.push((
+            name,
+            read_cargo_nested_value_in_arena(r, arena, depth, buf)?,
+        ));
+        skip_cargo_whitespace(r)?;
+        expect_cargo_more(r, opened_at)?;
+        let at = r.pos();
+        match r.next()? {
+            Some(CARGO_COMMA) if read_cargo_trailing_close(r, CARGO_RBRACE)? => break,
+            Some(CARGO_COMMA) => skip_cargo_whitespace(r)?,
+            Some(CARGO_RBRACE) => break,
+            found => return Err(cargo_unexpected(found, at)),
+        }
+    }
+    if !r.config().preserve_order {
+        members.sort_by(|a, b| a.0.cmp(b.0));
+    }
+    Ok(CargoValueRef::Object(members.into_bump_slice()))
+}
+
+/*
+ * The tokens that make up Cargo text, as read by read_cargo_token. A string is
+ * given with its escapes decoded, and a number exactly as it appears.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CargoToken {
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    StringLit(String),
+    NumberLit(String),
+    True,
+    False,
+    Null,
+}
+
+/*
+ * Where a token appears in the input: the line and column of its first character
+ * and of the character just after it.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CargoSpan {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+/*
+ * Skips any whitespace and reads the next token, or returns None at the end of
+ * the input. Only the tokens themselves are checked, not the order in which they
+ * appear, so this is suitable for tools such as syntax highlighters that must
+ * cope with text that is not (yet) valid.
+ */
+pub fn read_cargo_token<R: CargoRead>(
+    r: &mut R,
+) -> Result<Option<(CargoToken, CargoSpan)>, CargoError> {
+    skip_cargo_whitespace(r)?;
+    let start = r.pos();
+    let punctuation = match r.peek()? {
+        None => return Ok(None),
+        Some(CARGO_LBRACE) => Some(CargoToken::LBrace),
+        Some(CARGO_RBRACE) => Some(CargoToken::RBrace),
+        Some(CARGO_LBRACK) => Some(CargoToken::LBracket),
+        Some(CARGO_RBRACK) => Some(CargoToken::RBracket),
+        Some(CARGO_COLON) => Some(CargoToken::Colon),
+        Some(CARGO_COMMA) => Some(CargoToken::Comma),
+        Some(_) => None,
+    };
+    let token = match punctuation {
+        Some(token) => {
+            r.next()?;
+            token
+        }
+        None => match read_cargo_scalar(r)? {
+            CargoContent::String(string) => CargoToken::StringLit(string.content),
+            CargoContent::Number(number) => CargoToken::NumberLit(
+                number
+                    .string_value
+                    .map(|text| text.content)
+                    .unwrap_or_default(),
+            ),
+            CargoContent::Basic(CargoBasic::CargoTrue(_)) => CargoToken::True,
+            CargoContent::Basic(CargoBasic::CargoFalse(_)) => CargoToken::False,
+            CargoContent::Basic(CargoBasic::CargoNull) => CargoToken::Null,
+            CargoContent::Object(_) | CargoContent::Array(_) => unreachable!(),
+        },
+    };
+    let end = r.pos();
+    Ok(Some((token, CargoSpan { start, end })))
+}
+
+impl CargoToken {
+    /*
+     * The character that the token starts with, for reporting it where it does
+     * not belong.
+     */
+    fn first_char(&self) -> char {
+        match self {
+            CargoToken::LBrace => CARGO_LBRACE,
+            CargoToken::RBrace => CARGO_RBRACE,
+            CargoToken::LBracket => CARGO_LBRACK,
+            CargoToken::RBracket => CARGO_RBRACK,
+            CargoToken::Colon => CARGO_COLON,
+            CargoToken::Comma => CARGO_COMMA,
+            CargoToken::StringLit(_) => CARGO_QUOTE,
+            CargoToken::NumberLit(text) => text.chars().next().unwrap_or(CARGO_DIGIT0),
+            CargoToken::True => CARGO_T,
+            CargoToken::False => CARGO_F,
+            CargoToken::Null => CARGO_N,
+        }
+    }
+}
+
+/*
+ * What read_cargo_all_errors expects the next token to be. After a comma, a
+ * closing bracket or brace is also allowed in relaxed mode, so the states with
+ * "OrClose" are used there too.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CargoExpect {
+    Value,
+    ValueOrClose,
+    Name,
+    NameOrClose,
+    Colon,
+    CommaOrClose,
+    End,
+}
+
+/*
+ * What is expected once a value has been read within the given open containers.
+ */
+fn cargo_expect_after_value(open: &[(bool, (usize, usize))]) -> CargoExpect {
+    if open.is_empty() {
+        CargoExpect::End
+    } else {
+        CargoExpect::CommaOrClose
+    }
+}
+
+/*
+ * Reads the whole of the input, checking that it is a single valid value as
+ * read_cargo_value and read_cargo_eof would, but carrying on after each error
+ * to find any others, and returns all of the errors in the order in which they
+ * were found. The input is read as a sequence of tokens, keeping a stack of the
+ * open containers. A token that is not allowed where it appears is reported as
+ * an UnexpectedChar, and then taken to mean what it most likely does: a comma
+ * starts the next element or member, a closing bracket or brace closes the
+ * innermost container of its kind, and so on. A token that is itself invalid is
+ * skipped up to the next whitespace or punctuation and treated as a value.
+ * The max_depth and reject_duplicate_keys options are not applied. If reading
+ * the input fails, the Io error is the last one returned.
+ */
+pub fn read_cargo_all_errors<R: CargoRead>(r: &mut R) -> Vec<CargoError> {
+    let mut errors = Vec::new();
+    // For each open container, true if it is an object and false if an array,
+    // with the position of its opening brace or bracket.
+    let mut open: Vec<(bool, (usize, usize))> = Vec::new();
+    let mut expect = CargoExpect::Value;
+    loop {
+        let before = r.pos();
+        let (token, span) = match read_cargo_token(r) {
+            Ok(Some(token)) => token,
+            Ok(None) => break,
+            #[cfg(feature = "std")]
+            Err(err @ CargoError::Io(_)) => {
+                errors.push(err);
+                return errors;
+            }
+            Err(err) => {
+                errors.push(err);
+                if let Err(err) = skip_cargo_bad_token(r, before) {
+                    errors.push(err);
+                    return errors;
+                }
+                if expect != CargoExpect::End {
+                    expect = cargo_expect_after_value(&open);
+                }
+                continue;
+            }
+        };
+        let in_object = open.last().map(|&(is_object, _)| is_object);
+        let after_comma = if r.config().relaxed {
+            (CargoExpect::ValueOrClose, CargoExpect::NameOrClose)
+        } else {
+            (CargoExpect::Value, CargoExpect::Name)
+        };
+        let next = match (&token, expect, in_object) {
+            (CargoToken::LBrace, CargoExpect::Value | CargoExpect::ValueOrClose, _) => {
+                open.push((true, span.start));
+                Some(CargoExpect::NameOrClose)
+            }
+            (CargoToken::LBracket, CargoExpect::Value | CargoExpect::ValueOrClose, _) => {
+                open.push((false, span.start));
+                Some(CargoExpect::ValueOrClose)
+            }
+            (CargoToken::StringLit(_), CargoExpect::Name | CargoExpect::NameOrClose, _) => {
+                Some(CargoExpect::Colon)
+            }
+            (CargoToken::Colon, CargoExpect::Colon, _) => Some(CargoExpect::Value),
+            (CargoToken::Comma, CargoExpect::CommaOrClose, Some(true)) => Some(after_comma.1),
+            (CargoToken::Comma, CargoExpect::CommaOrClose, Some(false)) => Some(after_comma.0),
+            (
+                CargoToken::RBrace,
+                CargoExpect::NameOrClose | CargoExpect::CommaOrClose,
+                Some(true),
+            )
+            | (
+                CargoToken::RBracket,
+                CargoExpect::ValueOrClose | CargoExpect::CommaOrClose,
+                Some(false),
+            ) => {
+                open.pop();
+                Some(cargo_expect_after_value(&open))
+            }
+            (
+                CargoToken::StringLit(_)
+                | CargoToken::NumberLit(_)
+                | CargoToken::True
+                | CargoToken::False
+                | CargoToken::Null,
+                CargoExpect::Value | CargoExpect::ValueOrClose,
+                _,
+            ) => Some(cargo_expect_after_value(&open)),
+            _ => None,
+        };
+        if let Some(next) = next {
+            expect = next;
+            continue;
+        }
+        errors.push(CargoError::UnexpectedChar {
+            found: token.first_char(),
+            at: span.start,
+        });
+        expect = match (token, in_object) {
+            (CargoToken::LBrace, _) => {
+                open.push((true, span.start));
+                CargoExpect::NameOrClose
+            }
+            (CargoToken::LBracket, _) => {
+                open.push((false, span.start));
+                CargoExpect::ValueOrClose
+            }
+            (close @ (CargoToken::RBrace | CargoToken::RBracket), _) => {
+                let is_object = close == CargoToken::RBrace;
+                match open.iter().rposition(|&(kind, _)| kind == is_object) {
+                    Some(i) => {
+                        open.truncate(i);
+                        cargo_expect_after_value(&open)
+                    }
+                    None => expect,
+                }
+            }
+            (CargoToken::Comma, Some(true)) => CargoExpect::Name,
+            (CargoToken::Comma, Some(false)) => CargoExpect::Value,
+            (CargoToken::Colon, Some(true)) => CargoExpect::Value,
+            (CargoToken::Comma | CargoToken::Colon, _) => expect,
+            // A name where a comma was expected is most likely missing the comma.
+            (CargoToken::StringLit(_), Some(true)) if expect == CargoExpect::CommaOrClose => {
+                CargoExpect::Colon
+            }
+            _ if expect == CargoExpect::End => CargoExpect::End,
+            _ => cargo_expect_after_value(&open),
+        };
+    }
+    if let Some(&(_, opened_at)) = open.last() {
+        errors.push(CargoError::UnterminatedContainer { opened_at });
+    } else if expect == CargoExpect::Value {
+        errors.push(CargoError::UnexpectedEof { at: r.pos() });
+    }
+    errors
+}
+
+/*
+ * Skips the rest of a token that could not be read, which started at or after
+ * "start", up to the next whitespace or punctuation. At least one character is
+ * skipped, so that the same error is not found again.
+ */
+fn skip_cargo_bad_token<R: CargoRead>(r: &mut R, start: (usize, usize)) -> Result<(), CargoError> {
+    if r.pos() == start {
+        r.next()?;
+    }
+    while let Some(c) = r.peek()? {
+        if cargo_is_whitespace(c)
+            || [
+                CARGO_LBRACE,
+                CARGO_RBRACE,
+                CARGO_LBRACK,
+                CARGO_RBRACK,
+                CARGO_COLON,
+                CARGO_COMMA,
+            ]
+            .contains(&c)
+        {
+            break;
+        }
+        r.next()?;
+    }
+    Ok(())
+}
+
+/*
+ * The events reported by read_cargo_events, in the order in which the tokens
+ * they correspond to appear in the input. Each member of an object is reported
+ * as a Key event followed by the events for its value.
+ */
+#[derive(Debug)]
+pub enum CargoEvent {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    Str(String),
+    Number(CargoNumber),
+    Bool(bool),
+    Null,
+}
+
+/*
+ * Reads a single value of any type, calling "cb" with an event for each token as
+ * it is recognized instead of building a CargoValue. Rather than recursing into
+ * nested objects and arrays, we keep a stack of the containers that are still
+ * open, so only the stack grows with the depth of nesting. If "cb" returns an
+ * error, reading stops and that error is returned.
+ */
+pub fn read_cargo_events<R: CargoRead, F: FnMut(CargoEvent) -> Result<(), CargoError>>(
+    r: &mut R,
+    cb: &mut F,
+) -> Result<(), CargoError> {
+    // For each open container, true if it is an object and false if an array,
+    // with the position of its opening brace or bracket.
+    let mut open: Vec<(bool, (usize, usize))> = Vec::new();
+    loop {
+        skip_cargo_whitespace(r)?;
+        if let Some(&(_, opened_at)) = open.last() {
+            expect_cargo_more(r, opened_at)?;
+        }
+        let opened_at = r.pos();
+        match r.peek()? {
+            Some(CARGO_LBRACE) => {
+                check_cargo_depth(r, open.len() + 1)?;
+                r.next()?;
+                cb(CargoEvent::StartObject)?;
+                skip_cargo_whitespace(r)?;
+                if r.peek()? == Some(CARGO_RBRACE) {
+                    r.next()?;
+                    cb(CargoEvent::EndObject)?;
+                } else {
+                    open.push((true, opened_at));
+                    read_cargo_key_event(r, opened_at, cb)?;
+                    continue;
+                }
+            }
+            Some(CARGO_LBRACK) => {
+                check_cargo_depth(r, open.len() + 1)?;
+                r.next()?;
+                cb(CargoEvent::StartArray)?;
+                skip_cargo_whitespace(r)?;
+                if r.peek()? == Some(CARGO_RBRACK) {
+                    r.next()?;
+                    cb(CargoEvent::EndArray)?;
+                } else {
+                    open.push((false, opened_at));
+                    continue;
+                }
+            }
+            _ => cb(match read_cargo_scalar(r)? {
+                CargoContent::String(string) => CargoEvent::Str(string.content),
+                CargoContent::Number(number) => CargoEvent::Number(number),
+                CargoContent::Basic(CargoBasic::CargoTrue(b) | CargoBasic::CargoFalse(b)) => {
+                    CargoEvent::Bool(b)
+                }
+                CargoContent::Basic(CargoBasic::CargoNull) => CargoEvent::Null,
+                CargoContent::Object(_) | CargoContent::Array(_) => unreachable!(),
+            })?,
+        }
+        // A value has been completed, so close any containers that end here.
+        loop {
+            let Some(&(is_object, opened_at)) = open.last() else {
+                return Ok(());
+            };
+            skip_cargo_whitespace(r)?;
+            expect_cargo_more(r, opened_at)?;
+            let at = r.pos();
+            let close = if is_object {
+                CARGO_RBRACE
+            } else {
+                CARGO_RBRACK
+            };
+            match r.next()? {
+                Some(CARGO_COMMA) if read_cargo_trailing_close(r, close)? => {
+                    open.pop();
+                    cb(if is_object {
+                        CargoEvent::EndObject
+                    } else {
+                        CargoEvent::EndArray
+                    })?;
+                }
+                Some(CARGO_COMMA) => {
+                    if is_object {
+                        read_cargo_key_event(r, opened_at, cb)?;
+                    }
+                    break;
+                }
+                Some(CARGO_RBRACE) if is_object => {
+                    open.pop();
+                    cb(CargoEvent::EndObject)?;
+                }
+                Some(CARGO_RBRACK) if !is_object => {
+                    open.pop();
+                    cb(CargoEvent::EndArray)?;
+                }
+                found => return Err(cargo_unexpected(found, at)),
+            }
+        }
+    }
+}
+
+/*
+ * Reads the name of an object member and the colon that follows it, within the
+ * object opened at "opened_at".
+ */
+fn read_cargo_key_event<R: CargoRead, F: FnMut(CargoEvent) -> Result<(), CargoError>>(
+    r: &mut R,
+    opened_at: (usize, usize),
+    cb: &mut F,
+) -> Result<(), CargoError> {
+    skip_cargo_whitespace(r)?;
+    expect_cargo_more(r, opened_at)?;
+    let name = read_cargo_string(r)?;
+    skip_cargo_whitespace(r)?;
+    expect_cargo_more(r, opened_at)?;
+    expect_cargo_char(r, CARGO_COLON)?;
+    cb(CargoEvent::Key(name.content))
+}
+
+/*
+ * Adapts an io::Write so that the write_cargo_* functions, which produce their
+ * output through fmt::Write, can write to it. Since a fmt::Error carries no
+ * information, the io::Error that caused it is kept so that it can be reported.
+ * The many small pieces of output are gathered in a BufWriter, so "failed" is
+ * set once a write to the underlying writer has failed, after which nothing
+ * more should be sent to it.
+ */
+#[cfg(feature = "std")]
+struct CargoIoWriter<W: io::Write> {
+    inner: io::BufWriter<W>,
+    error: Option<io::Error>,
+    failed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> CargoIoWriter<W> {
+    fn new(w: W, opts: &WriteOpts) -> Self {
+        Self {
+            inner: io::BufWriter::with_capacity(opts.buffer_size(), w),
+            error: None,
+            failed: false,
+        }
+    }
+    /*
+     * Flushes the output once writing has ended with the given result, and returns
+     * that result, or the error from flushing if it fails. After a failed write, the
+     * rest of the buffer is thrown away instead, since the BufWriter would otherwise
+     * try to write it again when dropped. When the input was invalid, whatever came
+     * before the error is still written out.
+     */
+    fn finish(self, result: Result<(), CargoError>) -> Result<(), CargoError> {
+        let mut inner = self.inner;
+        if self.failed {
+            let _ = inner.into_parts();
+            return result;
+        }
+        match result {
+            Ok(()) => Ok(io::Write::flush(&mut inner)?),
+            Err(err) => {
+                let _ = io::Write::flush(&mut inner);
+                Err(err)
+            }
+        }
+    }
+    fn take_error(&mut self) -> CargoError {
+        match self.error.take() {
+            Some(err) => CargoError::Io(err),
+            None => CargoError::Io(io::Error::other("value could not be formatted")),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> fmt::Write for CargoIoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        io::Write::write_all(&mut self.inner, s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            self.failed = true;
+            fmt::Error
+        })
+    }
+}
+
+/*
+ * Reads a single value of any type and writes it out in canonical form as it
+ * is read, rather than building a CargoValue and then writing that. The output
+ * is the same as write_cargo_value would produce, but only a small amount of
+ * state is kept for each open container, so memory use does not depend on the
+ * size of the value. Note that if the input turns out to be invalid, whatever
+ * came before the error will already have been written. The output is buffered
+ * as for to_writer, and w is flushed at the end.
+ */
+#[cfg(feature = "std")]
+pub fn write_cargo_events<R: CargoRead, W: io::Write>(
+    r: &mut R,
+    w: W,
+    opts: &WriteOpts,
+) -> Result<(), CargoError> {
+    let mut out = CargoIoWriter::new(w, opts);
+    // For each open container, whether it is an object and how many elements or
+    // members have been written to it so far.
+    let mut open: Vec<(bool, usize)> = Vec::new();
+    let mut result = read_cargo_events(r, &mut |event| {
+        write_cargo_event(&mut out, opts, &mut open, event).map_err(|_| out.take_error())
+    });
+    if result.is_ok() && opts.final_newline() {
+        result = (opts.line_ending)
+            .write_cargo_line_ending(&mut out)
+            .map_err(|_| out.take_error());
+    }
+    out.finish(result)
+}
+
+#[cfg(feature = "std")]
+fn write_cargo_event<W: fmt::Write>(
+    w: &mut W,
+    opts: &WriteOpts,
+    open: &mut Vec<(bool, usize)>,
+    event: CargoEvent,
+) -> fmt::Result {
+    match event {
+        CargoEvent::EndObject | CargoEvent::EndArray => {
+            open.pop();
+            write_cargo_newline(w, opts, open.len())?;
+            return w.write_char(match event {
+                CargoEvent::EndObject => CARGO_RBRACE,
+                _ => CARGO_RBRACK,
+            });
+        }
+        _ => {}
+    }
+    // Each member of an object starts with its key, and each element of an array
+    // with the value itself. These are separated from what came before by a comma
+    // and (when pretty-printing) a newline.
+    let depth = open.len();
+    if let Some((is_object, count)) = open.last_mut() {
+        if !*is_object || matches!(event, CargoEvent::Key(_)) {
+            if *count > 0 {
+                w.write_char(CARGO_COMMA)?;
+            }
+            *count += 1;
+            write_cargo_newline(w, opts, depth)?;
+        }
+    }
+    match event {
+        CargoEvent::StartObject => {
+            open.push((true, 0));
+            w.write_char(CARGO_LBRACE)
+        }
+        CargoEvent::StartArray => {
+            open.push((false, 0));
+            w.write_char(CARGO_LBRACK)
+        }
+        CargoEvent::Key(name) => {
+            write_cargo_colored(w, opts, CARGO_COLOR_NAME, |w| {
+                CargoString::from(name).write_cargo_string(w, opts)
+            })?;
+            w.write_char(CARGO_COLON)?;
+            if opts.indent.is_some() {
+                w.write_char(CARGO_SPACE)?;
+            }
+            Ok(())
+        }
+        CargoEvent::Str(string) => write_cargo_colored(w, opts, CARGO_COLOR_STRING, |w| {
+            CargoString::from(string).write_cargo_string(w, opts)
+        }),
+        CargoEvent::Number(number) => write_cargo_colored(w, opts, CARGO_COLOR_NUMBER, |w| {
+            number.write_cargo_number(w, opts)
+        }),
+        CargoEvent::Bool(b) => write_cargo_colored(w, opts, CARGO_COLOR_LITERAL, |w| {
+            w.write_str(if b {
+                CARGO_TRUE_TOKEN
+            } else {
+                CARGO_FALSE_TOKEN
+            })
+        }),
+        CargoEvent::Null => write_cargo_colored(w, opts, CARGO_COLOR_LITERAL, |w| {
+            w.write_str(CARGO_NULL_TOKEN)
+        }),
+        CargoEvent::EndObject | CargoEvent::EndArray => unreachable!(),
+    }
+}
+
+fn cargo_is_whitespace(c: char) -> bool {
+    c == CARGO_SPACE || c == CARGO_LF || c == CARGO_CR || c == CARGO_HT
+}
+
+fn cargo_is_exponent(c: char) -> bool {
+    c == CARGO_E || c == AsciiChar::E.as_char()
+}
+
+fn cargo_is_digit(c: char) -> bool {
+    c >= CARGO_DIGIT0 && c <= AsciiChar::_9.as_char()
+}
+
+fn cargo_is_hex(c: char) -> bool {
+    cargo_is_digit(c)
+        || (c >= AsciiChar::A.as_char() && c <= AsciiChar::F.as_char())
+        || (c >= AsciiChar::a.as_char() && c <= AsciiChar::f.as_char())
+}
+
+/*
+ * The control characters that must be escaped in strings are U+0000 through
+ * U+001F. DEL (U+007F) is not one of them.
+ */
+fn cargo_is_control(c: char) -> bool {
+    c >= AsciiChar::Null.as_char() && c < CARGO_SPACE
+}
+
+/*
+ * The characters that write_cargo_string writes as \u escapes when they have no
+ * short escape: all control characters and the soft hyphen (which is invisible),
+ * and everything beyond Latin-1.
+ */
+fn cargo_needs_unicode_escape(c: char) -> bool {
+    cargo_is_control(c)
+        || (AsciiChar::DEL.as_char()..='\u{9f}').contains(&c)
+        || c == '\u{ad}'
+        || c > '\u{ff}'
 }
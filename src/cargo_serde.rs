@@ -0,0 +1,152 @@
+use crate::cargo::{
+    CargoArray, CargoBasic, CargoContent, CargoNumber, CargoObject, CargoString, CargoValue,
+};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/*
+ * Values map onto the serde data model the same way that JSON does, so that
+ * for example a CargoValue can be converted to and from a serde_json::Value.
+ * Object members are serialized in order, including any duplicates.
+ */
+impl Serialize for CargoValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.content().serialize(serializer)
+    }
+}
+
+impl Serialize for CargoContent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CargoContent::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (name, value) in object.iter() {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            CargoContent::Array(array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for element in array.iter() {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            CargoContent::String(string) => string.serialize(serializer),
+            CargoContent::Number(number) => number.serialize(serializer),
+            CargoContent::Basic(CargoBasic::CargoNull) => serializer.serialize_unit(),
+            CargoContent::Basic(CargoBasic::CargoTrue(b) | CargoBasic::CargoFalse(b)) => {
+                serializer.serialize_bool(*b)
+            }
+        }
+    }
+}
+
+impl Serialize for CargoString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Serialize for CargoNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.as_i64(), self.as_f64()) {
+            (Some(i), _) => serializer.serialize_i64(i),
+            (None, Some(f)) => serializer.serialize_f64(f),
+            (None, None) => Err(serde::ser::Error::custom("number has no value")),
+        }
+    }
+}
+
+struct CargoValueVisitor;
+
+impl<'de> Visitor<'de> for CargoValueVisitor {
+    type Value = CargoValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<CargoValue, E> {
+        Ok(CargoValue::from(CargoContent::Basic(if b {
+            CargoBasic::CargoTrue(true)
+        } else {
+            CargoBasic::CargoFalse(false)
+        })))
+    }
+    fn visit_i64<E: de::Error>(self, i: i64) -> Result<CargoValue, E> {
+        Ok(CargoValue::from(CargoContent::Number(CargoNumber::from(i))))
+    }
+    fn visit_u64<E: de::Error>(self, u: u64) -> Result<CargoValue, E> {
+        let number = match i64::try_from(u) {
+            Ok(i) => CargoNumber::from(i),
+            Err(_) => CargoNumber::try_from(u as f64).map_err(E::custom)?,
+        };
+        Ok(CargoValue::from(CargoContent::Number(number)))
+    }
+    fn visit_f64<E: de::Error>(self, f: f64) -> Result<CargoValue, E> {
+        let number = CargoNumber::try_from(f).map_err(E::custom)?;
+        Ok(CargoValue::from(CargoContent::Number(number)))
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<CargoValue, E> {
+        self.visit_string(s.to_string())
+    }
+    fn visit_string<E: de::Error>(self, s: String) -> Result<CargoValue, E> {
+        Ok(CargoValue::from(CargoContent::String(CargoString::from(s))))
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<CargoValue, E> {
+        Ok(CargoValue::from(CargoContent::Basic(CargoBasic::CargoNull)))
+    }
+    fn visit_none<E: de::Error>(self) -> Result<CargoValue, E> {
+        self.visit_unit()
+    }
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<CargoValue, D::Error> {
+        CargoValue::deserialize(d)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CargoValue, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(CargoValue::from(CargoContent::Array(Box::new(
+            CargoArray::from(elements),
+        ))))
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<CargoValue, A::Error> {
+        let mut members = Vec::new();
+        while let Some(member) = map.next_entry::<String, CargoValue>()? {
+            members.push(member);
+        }
+        Ok(CargoValue::from(CargoContent::Object(Box::new(
+            CargoObject::from(members),
+        ))))
+    }
+}
+
+impl<'de> Deserialize<'de> for CargoValue {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(CargoValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for CargoContent {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        CargoValue::deserialize(d).map(CargoValue::into_content)
+    }
+}
+
+impl<'de> Deserialize<'de> for CargoString {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d).map(CargoString::from)
+    }
+}
+
+impl<'de> Deserialize<'de> for CargoNumber {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        match CargoValue::deserialize(d)?.into_content() {
+            CargoContent::Number(number) => Ok(number),
+            _ => Err(de::Error::custom("expected a number")),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use alloc::string::String;
+use core::{error::Error, fmt};
+#[cfg(feature = "std")]
+use std::io;
+
+/*
+ * Errors that can be produced while reading a Cargo value, converting one to a
+ * Rust value of a type that it does not have (a TypeMismatch), or looking up a
+ * JSON Pointer that it does not contain (a PointerNotFound), or resetting a
+ * CargoRead to a mark that it does not hold (a MarkLost), or making a number from
+ * a float that is a NaN or infinity (a NotFinite).
+ * Every syntax error records the (line, column) position in the input at which
+ * it was detected. Lines and columns are both counted starting from 1, except
+ * that when the input ends inside an object or array, the UnterminatedContainer
+ * error records the position of its opening brace or bracket instead.
+ */
+#[derive(Debug)]
+pub enum CargoError {
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    UnexpectedEof {
+        at: (usize, usize),
+    },
+    UnexpectedChar {
+        found: char,
+        at: (usize, usize),
+    },
+    InvalidEscape {
+        at: (usize, usize),
+    },
+    InvalidNumber {
+        at: (usize, usize),
+    },
+    InvalidUtf8 {
+        at: (usize, usize),
+    },
+    DuplicateKey(String),
+    ByteOrderMark,
+    LimitExceeded {
+        at: (usize, usize),
+    },
+    DepthExceeded {
+        at: (usize, usize),
+    },
+    UnterminatedContainer {
+        opened_at: (usize, usize),
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    PointerNotFound(String),
+    MarkLost {
+        at: (usize, usize),
+    },
+    NotFinite,
+}
+
+impl CargoError {
+    /*
+     * The (line, column) position at which the error was detected, for errors
+     * that have one.
+     */
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            CargoError::UnexpectedEof { at }
+            | CargoError::UnexpectedChar { at, .. }
+            | CargoError::InvalidEscape { at }
+            | CargoError::InvalidNumber { at }
+            | CargoError::InvalidUtf8 { at }
+            | CargoError::LimitExceeded { at }
+            | CargoError::DepthExceeded { at }
+            | CargoError::MarkLost { at }
+            | CargoError::UnterminatedContainer { opened_at: at } => Some(*at),
+            CargoError::ByteOrderMark => Some((1, 1)),
+            _ => None,
+        }
+    }
+}
+
+/*
+ * Every error is described in the same form, as "error at line L, column C:"
+ * followed by the reason, or just "error:" and the reason for an error that has
+ * no position, so that the output of -v is predictable.
+ */
+impl fmt::Display for CargoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position() {
+            Some((line, column)) => write!(f, "error at line {}, column {}: ", line, column)?,
+            None => f.write_str("error: ")?,
+        }
+        match self {
+            #[cfg(feature = "std")]
+            CargoError::Io(err) => write!(f, "i/o error: {}", err),
+            CargoError::UnexpectedEof { .. } => f.write_str("unexpected end of input"),
+            CargoError::UnexpectedChar { found, .. } => {
+                write!(f, "unexpected character {:?}", found)
+            }
+            CargoError::InvalidEscape { .. } => f.write_str("invalid escape sequence"),
+            CargoError::InvalidNumber { .. } => f.write_str("invalid number"),
+            CargoError::InvalidUtf8 { .. } => f.write_str("invalid UTF-8"),
+            CargoError::DuplicateKey(name) => write!(f, "duplicate member name {:?}", name),
+            CargoError::ByteOrderMark => f.write_str("input starts with a byte order mark"),
+            CargoError::LimitExceeded { .. } => f.write_str("string or number too long"),
+            CargoError::DepthExceeded { .. } => f.write_str("objects and arrays nested too deeply"),
+            CargoError::UnterminatedContainer { .. } => {
+                f.write_str("object or array opened here is never closed")
+            }
+            CargoError::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            CargoError::PointerNotFound(pointer) => write!(f, "no value at {:?}", pointer),
+            CargoError::MarkLost { .. } => f.write_str("no marked position to reset to"),
+            CargoError::NotFinite => f.write_str("NaN and infinities are not numbers"),
+        }
+    }
+}
+
+impl Error for CargoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            CargoError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for CargoError {
+    fn from(err: io::Error) -> Self {
+        CargoError::Io(err)
+    }
+}
@@ -0,0 +1,266 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("the \"alloc\" feature (or \"std\", which enables it) is required");
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(not(feature = "std"))]
+use cargo::Read;
+use core::iter;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+pub mod cargo;
+#[cfg(feature = "serde")]
+mod cargo_serde;
+mod error;
+
+pub use cargo::{
+    write_cargo_value, CargoArray, CargoBasic, CargoContent, CargoDiff, CargoDiffKind, CargoEvent,
+    CargoLayout, CargoNumber, CargoObject, CargoRead, CargoReader, CargoSpan, CargoStats,
+    CargoStrReader, CargoString, CargoToken, CargoValue, CargoValueType, CargoWhitespace,
+    IndentStyle, LineEnding, ParseConfig, WriteOpts,
+};
+pub use error::CargoError;
+
+#[cfg(feature = "bigdecimal")]
+pub use bigdecimal::BigDecimal;
+#[cfg(feature = "bumpalo")]
+pub use bumpalo::Bump as Arena;
+#[cfg(feature = "bumpalo")]
+pub use cargo::CargoValueRef;
+
+/*
+ * Reads a single Cargo value from the given input, which must contain nothing
+ * but whitespace after the value.
+ */
+pub fn parse(input: impl Read) -> Result<CargoValue, CargoError> {
+    from_reader_with_config(input, &ParseConfig::default())
+}
+
+/*
+ * Reads a single Cargo value from a byte slice in the same way as parse. This is
+ * available without the "std" feature, when parse can only read from slices too.
+ */
+pub fn parse_slice(input: &[u8]) -> Result<CargoValue, CargoError> {
+    parse(input)
+}
+
+/*
+ * Reads a single Cargo value from text that is already in memory in the same way
+ * as parse, but without the buffering and decoding that reading bytes requires.
+ */
+pub fn parse_str(input: &str) -> Result<CargoValue, CargoError> {
+    let mut reader = CargoStrReader::new(input);
+    let value = cargo::read_cargo_value(&mut reader)?;
+    cargo::read_cargo_eof(&mut reader)?;
+    Ok(value)
+}
+
+/*
+ * Reads a single Cargo value in the same way as parse, but accepting the input
+ * that is permitted by the given configuration.
+ */
+pub fn from_reader_with_config(
+    input: impl Read,
+    config: &ParseConfig,
+) -> Result<CargoValue, CargoError> {
+    let mut reader = CargoReader::with_config(input, config.clone());
+    let value = cargo::read_cargo_value(&mut reader)?;
+    cargo::read_cargo_eof(&mut reader)?;
+    Ok(value)
+}
+
+/*
+ * Reads a single Cargo value from the given input in the same way as parse, but
+ * into the given arena rather than with an allocation for each string, object
+ * and array, which is much faster for input with a great many small values. The
+ * value can be used for as long as the arena is, and is freed when the arena is
+ * dropped or reset; CargoValueRef::to_value makes a CargoValue of it.
+ */
+#[cfg(feature = "bumpalo")]
+pub fn parse_in_arena<'a>(
+    input: impl Read,
+    arena: &'a Arena,
+) -> Result<CargoValueRef<'a>, CargoError> {
+    let mut reader = CargoReader::new(input);
+    let value = cargo::read_cargo_value_in_arena(&mut reader, arena)?;
+    cargo::read_cargo_eof(&mut reader)?;
+    Ok(value)
+}
+
+/*
+ * Reads a sequence of Cargo values from the given input, such as the lines of a
+ * newline-delimited log, and yields each of them in turn until the end of the
+ * input. The values may be separated by any amount of whitespace (or none, if
+ * that is unambiguous, as in {}[]). After an error, nothing more is yielded.
+ */
+pub fn parse_many(input: impl Read) -> impl Iterator<Item = Result<CargoValue, CargoError>> {
+    let mut reader = CargoReader::new(input);
+    let mut failed = false;
+    iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        let value = match cargo::read_cargo_more(&mut reader) {
+            Ok(false) => return None,
+            Ok(true) => cargo::read_cargo_value(&mut reader),
+            Err(err) => Err(err),
+        };
+        failed = value.is_err();
+        Some(value)
+    })
+}
+
+/*
+ * Splits the given input into tokens, yielding each with its span until the end
+ * of the input. The tokens need not form a valid value. After an error, nothing
+ * more is yielded.
+ */
+pub fn tokenize(
+    input: impl Read,
+) -> impl Iterator<Item = Result<(CargoToken, CargoSpan), CargoError>> {
+    let mut reader = CargoReader::new(input);
+    let mut failed = false;
+    iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        let token = cargo::read_cargo_token(&mut reader).transpose()?;
+        failed = token.is_err();
+        Some(token)
+    })
+}
+
+/*
+ * Reads input that consists of a single array, calling "f" on each element of
+ * the array in turn, so that an array of records far too large to be held in
+ * memory can be processed one record at a time. Input whose top-level value is
+ * not an array is rejected. If "f" returns an error, reading stops and that error
+ * is returned.
+ */
+pub fn for_each_element(
+    input: impl Read,
+    mut f: impl FnMut(&CargoValue) -> Result<(), CargoError>,
+) -> Result<(), CargoError> {
+    let mut reader = CargoReader::new(input);
+    cargo::read_cargo_elements(&mut reader, &mut f)?;
+    cargo::read_cargo_eof(&mut reader)
+}
+
+/*
+ * Reads a single Cargo value from the given input in the same way as parse, but
+ * reports it to "cb" as a sequence of events rather than building a CargoValue.
+ */
+pub fn parse_events(
+    input: impl Read,
+    cb: impl FnMut(CargoEvent) -> Result<(), CargoError>,
+) -> Result<(), CargoError> {
+    parse_events_with_config(input, &ParseConfig::default(), cb)
+}
+
+/*
+ * Reports the events for a single Cargo value in the same way as parse_events,
+ * but accepting the input that is permitted by the given configuration.
+ */
+pub fn parse_events_with_config(
+    input: impl Read,
+    config: &ParseConfig,
+    mut cb: impl FnMut(CargoEvent) -> Result<(), CargoError>,
+) -> Result<(), CargoError> {
+    let mut reader = CargoReader::with_config(input, config.clone());
+    cargo::read_cargo_events(&mut reader, &mut cb)?;
+    cargo::read_cargo_eof(&mut reader)
+}
+
+/*
+ * Checks that the input contains a single valid Cargo value, without building
+ * a CargoValue for it. This reads the input through the event parser and
+ * discards the events, so memory use does not grow with the size of the input
+ * (only with the length of the longest string and the depth of nesting).
+ */
+pub fn validate_only(input: impl Read) -> Result<(), CargoError> {
+    parse_events(input, |_| Ok(()))
+}
+
+/*
+ * Checks the input in the same way as validate_only, but rather than stopping at
+ * the first error, carries on to find the rest, and returns all of them in the
+ * order in which they were found (so none at all if the input is valid). The
+ * first error is normally the one that validate_only would report, and the others
+ * are found by guessing how the input should continue, which may of course be
+ * wrong; see read_cargo_all_errors.
+ */
+pub fn validate_all(input: impl Read) -> Vec<CargoError> {
+    cargo::read_cargo_all_errors(&mut CargoReader::new(input))
+}
+
+/*
+ * Whether the text is a single valid Cargo value, checked in the same way as by
+ * validate_only but reading the text directly.
+ */
+pub fn is_valid(input: &str) -> bool {
+    let mut reader = CargoStrReader::new(input);
+    cargo::read_cargo_events(&mut reader, &mut |_| Ok(()))
+        .and_then(|()| cargo::read_cargo_eof(&mut reader))
+        .is_ok()
+}
+
+/*
+ * Lists the differences between two values, each with the JSON Pointer of the
+ * place where it occurs. See cargo::diff_cargo_values for the details.
+ */
+pub fn diff(a: &CargoValue, b: &CargoValue) -> Vec<CargoDiff> {
+    cargo::diff_cargo_values(a, b)
+}
+
+/*
+ * Parses the given text and returns it in compact canonical form. Members of
+ * objects stay in the order in which they appear in the input.
+ */
+pub fn canonicalize_str(input: &str) -> Result<String, CargoError> {
+    Ok(parse_str(input)?.to_string())
+}
+
+/*
+ * Parses the given text and returns it pretty-printed in canonical form, with
+ * "indent" spaces for each level of indentation and a final newline.
+ */
+pub fn pretty_str(input: &str, indent: u32) -> Result<String, CargoError> {
+    Ok(parse_str(input)?.to_string_pretty(indent))
+}
+
+/*
+ * Reads a single Cargo value from the given input and writes it to the given
+ * output in canonical form, without building a CargoValue. This is what -c uses,
+ * so that it can handle documents of any size.
+ */
+#[cfg(feature = "std")]
+pub fn canonicalize_stream(
+    input: impl Read,
+    output: impl Write,
+    opts: &WriteOpts,
+) -> Result<(), CargoError> {
+    canonicalize_stream_with_config(input, output, opts, &ParseConfig::default())
+}
+
+/*
+ * Canonicalizes the input in the same way as canonicalize_stream, but accepting
+ * the input that is permitted by the given configuration.
+ */
+#[cfg(feature = "std")]
+pub fn canonicalize_stream_with_config(
+    input: impl Read,
+    output: impl Write,
+    opts: &WriteOpts,
+    config: &ParseConfig,
+) -> Result<(), CargoError> {
+    let mut reader = CargoReader::with_config(input, config.clone());
+    cargo::write_cargo_events(&mut reader, output, opts)?;
+    cargo::read_cargo_eof(&mut reader)
+}
@@ -1,18 +1,180 @@
-// use cargs::are_cargo_args_valid;
+use args::{CargoArgs, CargoMode, ColorChoice};
+use rs_cargo::{
+    write_cargo_value, CargoError, CargoLayout, CargoStats, IndentStyle, ParseConfig, WriteOpts,
+};
 use std::{
     env::{self},
-    io::{stdin, BufReader, Stdin},
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, stdin, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
 };
-// use std::fs::File;
 mod args;
-mod cargo;
 
-fn main() {
-    const USAGE: &str = "[-h] [-c|-v] [-p INDENT]\n \
+/*
+ * The exit status is 0 if the program ran successfully (including -h), EXIT_INVALID
+ * if the input could not be read or is not valid JSON, and EXIT_USAGE if the
+ * arguments are invalid, in which case the usage message is printed to standard
+ * error. Errors never go to standard output, so that it can be piped elsewhere.
+ * Each error is reported on a single line that starts with "cargo: " followed by
+ * the CargoError, as in "cargo: error at line 1, column 5: invalid number".
+ */
+const EXIT_INVALID: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+/*
+ * The width of a tab for --measure. Since -p only indents with spaces and a tab
+ * in a string is written as an escape, the output never contains one at present.
+ */
+const MEASURE_TAB_WIDTH: usize = 8;
+
+/*
+ * Reads and validates the input. In canonicalize mode, the input is written out
+ * in canonical form as it is read, either to standard output or to the file given
+ * with -o, so if it turns out to be invalid some output may already have been
+ * written by the time the error is reported. That output is buffered by the
+ * library and flushed before run returns, whether or not there is an error.
+ */
+fn run(args: &CargoArgs, reader: impl Read) -> Result<(), CargoError> {
+    if args.stats || args.select.is_some() || args.measure {
+        return run_with_value(args, reader);
+    }
+    if args.mode != CargoMode::Canonicalize {
+        return rs_cargo::parse_events_with_config(reader, &parse_config(args), |_| Ok(()));
+    }
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    rs_cargo::canonicalize_stream_with_config(reader, out, &write_opts(args), &parse_config(args))
+}
+
+/*
+ * Canonicalizes the file given with -f -i, which has already been read into
+ * "input", and replaces it with the result. The output is written to a temporary
+ * file beside the original, which is given the same permissions and renamed over
+ * it only once the whole of the input has been found valid. So if there is any
+ * error the original is left as it was, and the temporary file is removed.
+ */
+fn run_in_place(args: &CargoArgs, input: &[u8]) -> Result<(), CargoError> {
+    let path = args.input.as_deref().expect("-i is only accepted with -f");
+    let temp = in_place_temp_path(path);
+    let args = CargoArgs {
+        output: Some(temp.clone()),
+        ..args.clone()
+    };
+    let result = run(&args, input).and_then(|()| {
+        fs::set_permissions(&temp, fs::metadata(path)?.permissions())?;
+        Ok(fs::rename(&temp, path)?)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/*
+ * The temporary file for -i, which is in the same directory as the original so
+ * that renaming it over the original replaces it in a single step. The name is
+ * that of the original with a leading dot and the process ID after it.
+ */
+fn in_place_temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", process::id()));
+    path.with_file_name(name)
+}
+
+/*
+ * A --depth of 0 lifts the limit altogether, which needs the iterative parser for
+ * --stats (the other modes never recurse). Even so, a value read for --stats -c is
+ * written out recursively, so that combination is still limited by the stack.
+ */
+fn parse_config(args: &CargoArgs) -> ParseConfig {
+    match args.depth {
+        None => ParseConfig::default(),
+        Some(0) => ParseConfig::new().iterative(true).max_depth(None),
+        Some(n) => ParseConfig::new().max_depth(Some(n)),
+    }
+}
+
+fn write_opts(args: &CargoArgs) -> WriteOpts {
+    WriteOpts {
+        indent: args.indent.map(IndentStyle::Spaces),
+        precision: args.precision,
+        final_newline: (!args.final_newline).then_some(false),
+        color: args.indent.is_some() && use_color(args),
+        ..WriteOpts::default()
+    }
+}
+
+/*
+ * Whether pretty-printed output is to be colored. With --color auto (the default)
+ * it is only when standard output is a terminal and is not redirected by -o or
+ * -i, and NO_COLOR is not set to a nonempty value (see https://no-color.org).
+ */
+fn use_color(args: &CargoArgs) -> bool {
+    match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            args.output.is_none()
+                && !args.in_place
+                && io::stdout().is_terminal()
+                && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        }
+    }
+}
+
+/*
+ * As run, but the whole value is read into memory so that its statistics can be
+ * printed to standard error once it is known to be valid, or the part of it that
+ * --select points to can be found. The statistics are of the whole value, and
+ * only the selected part is written out, or just measured with --measure.
+ */
+fn run_with_value(args: &CargoArgs, reader: impl Read) -> Result<(), CargoError> {
+    let value = rs_cargo::from_reader_with_config(reader, &parse_config(args))?;
+    if args.stats {
+        eprintln!("{}", CargoStats::of(&value));
+    }
+    if args.mode != CargoMode::Canonicalize {
+        return Ok(());
+    }
+    let value = match &args.select {
+        Some(pointer) => value
+            .pointer(pointer)
+            .ok_or_else(|| CargoError::PointerNotFound(pointer.clone()))?,
+        None => &value,
+    };
+    if args.measure {
+        let opts = WriteOpts {
+            color: false,
+            ..write_opts(args)
+        };
+        println!("{}", CargoLayout::of(value, &opts, MEASURE_TAB_WIDTH));
+        return Ok(());
+    }
+    let mut out = String::new();
+    write_cargo_value(&mut out, value, &write_opts(args))
+        .expect("a value that has been read can always be written");
+    match &args.output {
+        Some(path) => File::create(path)?.write_all(out.as_bytes())?,
+        None => io::stdout().write_all(out.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    const USAGE: &str =
+        "[-h] [-c|-v] [-p INDENT] [-f FILE] [-o FILE] [--stats] [--max-precision N]\n \
+        [--no-final-newline] [--depth N] [--select PTR] [--color WHEN] [--measure]\n \
+        [--report-all] [-i]\n \
    -h       Help: displays this help menu.\n \
    -v       Validate: the program reads from standard input and checks whether\n \
             it is syntactically correct JSON.  If there is any error, then a message\n \
-            describing the error is printed to standard error before termination.\n \
+            describing the error is printed to standard error before termination,\n \
+            in the form 'cargo: error at line L, column C: REASON'.\n \
             No other output is produced.\n \
    -c       Canonicalize: once the input has been read and validated, it is\n \
             re-emitted to standard output in 'canonical form'.  Unless -p has been\n \
@@ -25,14 +187,107 @@ fn main() {
             The INDENT is an optional nonnegative integer argument that specifies the\n \
             number of additional spaces to be output at the beginning of a line for each\n \
             for each increase in indentation level.  If no value is specified, then a\n \
-            default value of 4 is used.\n";
-    let mut indent_level: i32 = 4;
+            default value of 4 is used.\n \
+   -f       File: the input is read from FILE instead of from standard input.\n \
+            If it is invalid, the line containing the error is shown as well, with\n \
+            a caret under the column at which the error was detected.\n \
+   -o       Output: with -c, the canonical output is written to FILE instead of to\n \
+            standard output.  The file is created or truncated as needed.\n \
+   -i       In place: with -c and -f, the canonical output replaces the contents\n \
+            of the input FILE.  If the input is invalid, the file is left as it\n \
+            was.  This may also be given as --in-place, but not with -o or\n \
+            --measure.\n \
+   --stats  Statistics: once the input has been read and validated, the number of\n \
+            values of each type and the maximum depth of nesting are printed to\n \
+            standard error.  This may be given with -v or -c, or on its own.\n \
+   --max-precision\n \
+            Precision: with -c, numbers that are not integers are written with at\n \
+            most N significant digits (between 1 and 17) rather than 15.\n \
+   --no-final-newline\n \
+            With -c -p, the pretty-printed output does not end with a newline.\n \
+            (Compact output never does.)\n \
+   --depth  Depth: objects and arrays may be nested at most N deep, so that\n \
+            --depth 1 allows [1, 2] but not [[1], 2].  Without it N is 128, and\n \
+            if N is 0 there is no limit at all.\n \
+   --select Select: only the value that the JSON Pointer PTR (such as /users/0/name)\n \
+            refers to is written out in canonical form, honoring -p.  This implies\n \
+            -c, and if there is no such value an error is reported.\n \
+   --color  Color: with -c -p, names, strings, numbers and literals are shown in\n \
+            different colors.  WHEN is auto (the default, which colors the output\n \
+            only on a terminal and not if NO_COLOR is set), always or never.\n \
+   --measure\n \
+            Measure: with -c, instead of the output itself, the number of lines it\n \
+            would have and the width of its longest line are printed, as in\n \
+            'lines: 12' and 'max width: 80'.\n \
+   --report-all\n \
+            Report all: with -v, the input is checked to the end rather than up to\n \
+            the first error, and every error found is reported.  Errors after the\n \
+            first are found by guessing how the input was meant to go on, and\n \
+            --depth is not applied.\n\n \
+EXIT STATUS: 0 on success, 1 if the input is invalid or cannot be read, and\n \
+2 if the arguments are invalid.\n";
     let argv: Vec<String> = env::args().collect();
-    let argc: usize = argv.len();
-    let reader: BufReader<Stdin> = BufReader::new(stdin());
-    dbg!(argv);
-    let is_valid: bool = args::are_cargo_args_valid(argc, argv.clone());
-    if !is_valid {
+    let args = match args::parse_args(&argv) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    if args.mode == CargoMode::Help {
         println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    // A file is read into memory first, so that the line with an error in it can
+    // be shown. Standard input is read as it arrives, since it may be very long.
+    let input = match &args.input {
+        Some(path) => match fs::read(path) {
+            Ok(bytes) => Some(bytes),
+            Err(err) => {
+                eprintln!("cargo: error: {}: {}", path.display(), err);
+                return ExitCode::from(EXIT_INVALID);
+            }
+        },
+        None => None,
+    };
+    let errors = match (&input, args.report_all) {
+        (Some(bytes), true) => rs_cargo::validate_all(&bytes[..]),
+        (None, true) => rs_cargo::validate_all(stdin()),
+        (Some(bytes), false) if args.in_place => {
+            run_in_place(&args, bytes).err().into_iter().collect()
+        }
+        (Some(bytes), false) => run(&args, &bytes[..]).err().into_iter().collect(),
+        (None, false) => run(&args, stdin()).err().into_iter().collect(),
+    };
+    for err in &errors {
+        eprintln!("cargo: {}", err);
+        if let (Some(bytes), Some(at)) = (&input, err.position()) {
+            show_error_line(bytes, at);
+        }
     }
+    if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_INVALID)
+    }
+}
+
+/*
+ * Prints the line of the input at which an error was detected, with a caret on
+ * the next line under the column. Any tabs before that column are repeated in the
+ * caret's line so that the caret lines up however wide the tabs are displayed.
+ */
+fn show_error_line(input: &[u8], (line, column): (usize, usize)) {
+    let text = String::from_utf8_lossy(input);
+    let Some(source) = text.split('\n').nth(line - 1) else {
+        return;
+    };
+    let source = source.strip_suffix('\r').unwrap_or(source);
+    let pad: String = source
+        .chars()
+        .take(column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    eprintln!("  {}\n  {}^", source, pad);
 }
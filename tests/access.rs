@@ -0,0 +1,658 @@
+use rs_cargo::{
+    diff, from_reader_with_config, write_cargo_value, CargoArray, CargoBasic, CargoContent,
+    CargoDiff, CargoDiffKind, CargoError, CargoObject, CargoStats, CargoString, CargoValue,
+    CargoValueType, ParseConfig, WriteOpts,
+};
+
+fn value(s: &str) -> CargoValue {
+    s.parse().unwrap()
+}
+
+#[test]
+fn accessors_on_matching_variants() {
+    assert_eq!(value("\"caf\"").content().as_str(), Some("caf"));
+    assert_eq!(value("-42").content().as_i64(), Some(-42));
+    assert_eq!(value("-42").content().as_f64(), Some(-42.0));
+    assert_eq!(value("2.5").content().as_f64(), Some(2.5));
+    assert_eq!(value("true").content().as_bool(), Some(true));
+    assert_eq!(value("false").content().as_bool(), Some(false));
+    let array = value("[1,\"two\"]");
+    let elements = array.content().as_array().unwrap();
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[1].content().as_str(), Some("two"));
+    assert!(value("{\"a\":1}").content().as_object().is_some());
+}
+
+#[test]
+fn accessors_on_other_variants() {
+    let null = value("null");
+    let content = null.content();
+    assert_eq!(content.as_str(), None);
+    assert_eq!(content.as_i64(), None);
+    assert_eq!(content.as_f64(), None);
+    assert_eq!(content.as_bool(), None);
+    assert!(content.as_array().is_none());
+    assert!(content.as_object().is_none());
+    assert_eq!(value("2.5").content().as_i64(), None);
+    assert_eq!(value("1e2").content().as_i64(), None);
+    assert!(value("[]").content().as_object().is_none());
+    assert!(value("{}").content().as_array().is_none());
+}
+
+#[test]
+fn object_get_by_name() {
+    let object = value("{\"name\":\"cargo\",\"version\":1}");
+    assert_eq!(
+        object.content().get("name").unwrap().content().as_str(),
+        Some("cargo")
+    );
+    let members = object.content().as_object().unwrap();
+    assert_eq!(members.get("version").unwrap().content().as_i64(), Some(1));
+    assert!(members.get("missing").is_none());
+    assert!(value("[1]").content().get("name").is_none());
+}
+
+#[test]
+fn object_get_duplicate_key_last_wins() {
+    let object = value("{\"a\":1,\"b\":2,\"a\":3}");
+    assert_eq!(
+        object.content().get("a").unwrap().content().as_i64(),
+        Some(3)
+    );
+}
+
+#[test]
+fn array_get_by_index() {
+    let array = value("[10,20,30]");
+    let elements = match array.content() {
+        CargoContent::Array(array) => array,
+        _ => unreachable!(),
+    };
+    assert_eq!(elements.len(), 3);
+    assert!(!elements.is_empty());
+    assert_eq!(elements.get(0).unwrap().content().as_i64(), Some(10));
+    assert_eq!(elements[2].content().as_i64(), Some(30));
+    assert!(elements.get(3).is_none());
+}
+
+#[test]
+#[should_panic(expected = "index 3 out of bounds for array of length 3")]
+fn array_index_out_of_bounds_panics() {
+    let array = value("[10,20,30]");
+    if let CargoContent::Array(array) = array.content() {
+        let _ = &array[3];
+    }
+}
+
+#[test]
+fn iterate_array_elements() {
+    let array = value("[1, 2, 3.5, 4]");
+    let array = match array.content() {
+        CargoContent::Array(array) => &**array,
+        _ => unreachable!(),
+    };
+    let sum: f64 = array.iter().filter_map(|v| v.content().as_f64()).sum();
+    assert_eq!(sum, 10.5);
+    let mut count = 0;
+    for element in array {
+        assert!(element.name().is_none());
+        count += 1;
+    }
+    assert_eq!(count, 4);
+}
+
+#[test]
+fn iterate_object_members() {
+    let object = value("{\"b\":1,\"c\":2,\"a\":3}");
+    let mut keys: Vec<&str> = object
+        .content()
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(name, _)| name)
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[test]
+fn string_length_is_in_bytes() {
+    for (input, text, len) in [
+        ("\"\"", "", 0),
+        ("\"cargo\"", "cargo", 5),
+        ("\"caf\\u00e9\"", "café", 5),
+        ("\"日本語\"", "日本語", 9),
+        ("\"\\ud83d\\ude00!\"", "😀!", 5),
+    ] {
+        let value = value(input);
+        let string = match value.content() {
+            CargoContent::String(string) => string,
+            _ => unreachable!(),
+        };
+        assert_eq!(string.as_str(), text);
+        assert_eq!(string.len(), len, "{}", input);
+        assert_eq!(string.is_empty(), len == 0);
+        assert!(string.capacity() >= string.len());
+    }
+    let string = CargoString::from(String::from("日本"));
+    assert_eq!(string.len(), 6);
+    assert!(string.capacity() >= 6);
+}
+
+#[test]
+fn string_capacity_covers_length() {
+    let mut text = String::new();
+    let mut capacities = Vec::new();
+    for i in 0..20_000 {
+        text.push(['a', 'é', '語', '😀'][i % 4]);
+        if i % 97 != 0 {
+            continue;
+        }
+        let value = value(&format!("\"{}\"", text));
+        let string = match value.content() {
+            CargoContent::String(string) => string,
+            _ => unreachable!(),
+        };
+        assert_eq!(string.as_str(), text);
+        assert_eq!(string.len(), text.len());
+        assert!(string.capacity() >= string.len(), "{}", string.len());
+        capacities.push(string.capacity());
+    }
+    // The capacity grows in chunks, so it is the same for many of the lengths.
+    capacities.dedup();
+    assert!(capacities.len() < 30, "{:?}", capacities);
+    // A clone is no larger than its content needs, and says so.
+    let value = value("\"abc\"");
+    let string = match value.content() {
+        CargoContent::String(string) => string,
+        _ => unreachable!(),
+    };
+    let copy = string.clone();
+    assert_eq!(copy, *string);
+    assert!(copy.capacity() >= copy.len());
+    assert!(copy.capacity() < string.capacity(), "{}", string.capacity());
+}
+
+#[test]
+fn clone_is_independent_of_original() {
+    let original = value("{\"a\": {\"b\": [1, 2], \"c\": \"x\"}, \"d\": null}");
+    let mut copy = original.clone();
+    assert_eq!(copy, original);
+    let mut inner = original.content().get("a").unwrap().clone();
+    assert_eq!(inner.name(), Some("a"));
+    inner.set_content(CargoContent::Basic(CargoBasic::CargoTrue(true)));
+    assert_eq!(inner.name(), Some("a"));
+    assert_eq!(inner.content().as_bool(), Some(true));
+    let mut object = original
+        .content()
+        .get("a")
+        .unwrap()
+        .content()
+        .as_object()
+        .unwrap()
+        .clone();
+    object
+        .get_mut("c")
+        .unwrap()
+        .set_content(CargoContent::String(CargoString::from(String::from("y"))));
+    assert_eq!(object.get("c").unwrap().content().as_str(), Some("y"));
+    copy.set_content(CargoContent::Object(Box::new(object)));
+    assert_eq!(copy.to_string(), "{\"b\":[1,2],\"c\":\"y\"}");
+    assert_eq!(
+        original.to_string(),
+        "{\"a\":{\"b\":[1,2],\"c\":\"x\"},\"d\":null}"
+    );
+}
+
+#[test]
+fn type_names() {
+    for (input, name, cargo_type) in [
+        ("{}", "object", CargoValueType::CargoObjectType),
+        ("[]", "array", CargoValueType::CargoArrayType),
+        ("\"s\"", "string", CargoValueType::CargoStringType),
+        ("-1.5", "number", CargoValueType::CargoNumberType),
+        ("true", "boolean", CargoValueType::CargoBasicType),
+        ("false", "boolean", CargoValueType::CargoBasicType),
+        ("null", "null", CargoValueType::CargoBasicType),
+    ] {
+        let value = value(input);
+        assert_eq!(value.type_name(), name, "{}", input);
+        assert_eq!(value.cargo_type(), cargo_type, "{}", input);
+    }
+    assert_eq!(CargoValue::null().type_name(), "null");
+}
+
+#[test]
+fn type_predicates() {
+    for (input, name) in [
+        ("{}", "object"),
+        ("[]", "array"),
+        ("\"s\"", "string"),
+        ("-1.5", "number"),
+        ("true", "boolean"),
+        ("false", "boolean"),
+        ("null", "null"),
+    ] {
+        let value = value(input);
+        assert_eq!(value.is_null(), name == "null", "{}", input);
+        assert_eq!(value.is_bool(), name == "boolean", "{}", input);
+        assert_eq!(value.is_number(), name == "number", "{}", input);
+        assert_eq!(value.is_string(), name == "string", "{}", input);
+        assert_eq!(value.is_array(), name == "array", "{}", input);
+        assert_eq!(value.is_object(), name == "object", "{}", input);
+        let container = name == "array" || name == "object";
+        assert_eq!(value.is_container(), container, "{}", input);
+        assert_eq!(value.is_scalar(), !container, "{}", input);
+    }
+}
+
+#[test]
+fn stats_count_values_and_depth() {
+    let stats = CargoStats::of(&value("[[[]], {\"a\": [\"x\", 1, true, null]}]"));
+    assert_eq!(
+        stats,
+        CargoStats {
+            objects: 1,
+            arrays: 4,
+            strings: 1,
+            numbers: 1,
+            booleans: 1,
+            nulls: 1,
+            values: 9,
+            max_depth: 3,
+        }
+    );
+    assert_eq!(CargoStats::of(&value("7")).max_depth, 0);
+    assert_eq!(CargoStats::of(&value("7")).values, 1);
+    assert_eq!(CargoStats::of(&value("{}")).max_depth, 1);
+}
+
+#[test]
+fn pointer_lookups() {
+    let doc =
+        value("{\"foo\":[\"bar\",{\"baz\":[10,20]}],\"\":0,\"a/b\":1,\"m~n\":2,\"~1\":3,\" \":4}");
+    assert_eq!(doc.pointer(""), Some(&doc));
+    assert_eq!(
+        doc.pointer("/foo/0").unwrap().content().as_str(),
+        Some("bar")
+    );
+    assert_eq!(
+        doc.pointer("/foo/1/baz/1").unwrap().content().as_i64(),
+        Some(20)
+    );
+    assert_eq!(doc.pointer("/foo/1/baz").unwrap().name(), Some("baz"));
+    assert_eq!(doc.pointer("/").unwrap().content().as_i64(), Some(0));
+    assert_eq!(doc.pointer("/a~1b").unwrap().content().as_i64(), Some(1));
+    assert_eq!(doc.pointer("/m~0n").unwrap().content().as_i64(), Some(2));
+    assert_eq!(doc.pointer("/~01").unwrap().content().as_i64(), Some(3));
+    assert_eq!(doc.pointer("/ ").unwrap().content().as_i64(), Some(4));
+    for missing in [
+        "foo",
+        "/bar",
+        "/foo/2",
+        "/foo/-",
+        "/foo/01",
+        "/foo/+1",
+        "/foo/x",
+        "/foo/0/bar",
+        "/a/b",
+        "/m~2n",
+        "/m~",
+        "/foo/1/baz/0/x",
+    ] {
+        assert!(doc.pointer(missing).is_none(), "{}", missing);
+    }
+}
+
+#[test]
+fn visit_mut_rewrites_values() {
+    let mut doc = value("{\"name\":\"cargo\",\"tags\":[\"a\",{\"b\":\"c\"}],\"n\":1,\"ok\":true}");
+    doc.visit_mut(&mut |v| {
+        if let Some(s) = v.content().as_str() {
+            let upper = s.to_uppercase();
+            v.set_content(CargoContent::String(CargoString::from(upper)));
+        }
+    });
+    assert_eq!(
+        doc.to_string(),
+        "{\"name\":\"CARGO\",\"tags\":[\"A\",{\"b\":\"C\"}],\"n\":1,\"ok\":true}"
+    );
+    let mut seen = Vec::new();
+    doc.visit_mut(&mut |v| seen.push(v.name().unwrap_or(v.type_name()).to_string()));
+    assert_eq!(
+        seen,
+        ["object", "name", "tags", "string", "object", "b", "n", "ok"]
+    );
+}
+
+#[test]
+fn normalize_numbers_rewrites_whole_floats() {
+    let mut doc = value("[5.0, 6e0, 3.5]");
+    doc.normalize_numbers();
+    assert_eq!(doc.to_string(), "[5,6,0.35e1]");
+    let elements = doc.content().as_array().unwrap();
+    assert_eq!(elements[0].content().as_i64(), Some(5));
+    assert_eq!(elements[2].content().as_i64(), None);
+    let mut doc = value(
+        "{\"a\": [-0.0, 1E2, 0.5e1, {\"b\": -120e-1}], \"c\": 1e19, \
+         \"d\": -9223372036854775808.0, \"e\": 2E19, \"f\": 7}",
+    );
+    doc.normalize_numbers();
+    assert_eq!(
+        doc.to_string(),
+        "{\"a\":[0,100,5,{\"b\":-12}],\"c\":0.1e20,\
+         \"d\":-9223372036854775808,\"e\":0.2e20,\"f\":7}"
+    );
+    assert_eq!(
+        doc.pointer("/d").unwrap().content().as_i64(),
+        Some(i64::MIN)
+    );
+    // One more than i64::MAX, which is 2^63, does not fit.
+    let mut big = value("9223372036854775808.0");
+    big.normalize_numbers();
+    assert_eq!(big.content().as_i64(), None);
+    // Numbers keep their text for verbatim mode.
+    let opts = WriteOpts {
+        verbatim_numbers: true,
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, doc.pointer("/a/1").unwrap(), &opts).unwrap();
+    assert_eq!(out, "1E2");
+}
+
+#[test]
+fn visit_mut_handles_deep_nesting() {
+    let config = ParseConfig {
+        iterative: true,
+        max_depth: None,
+        ..ParseConfig::default()
+    };
+    let depth = 50000;
+    let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    let mut doc = from_reader_with_config(input.as_bytes(), &config).unwrap();
+    let mut count = 0;
+    doc.visit_mut(&mut |_| count += 1);
+    assert_eq!(count, depth);
+}
+
+#[test]
+fn walk_yields_pointer_paths() {
+    let doc = value("{\"a\": [1, {\"b\": null}], \"c/d\": {\"e~f\": 2.5, \"\": []}, \"g\": \"s\"}");
+    let paths: Vec<String> = doc.walk().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        [
+            "",
+            "/a",
+            "/a/0",
+            "/a/1",
+            "/a/1/b",
+            "/c~1d",
+            "/c~1d/e~0f",
+            "/c~1d/",
+            "/g"
+        ]
+    );
+    for (path, node) in doc.walk() {
+        assert!(std::ptr::eq(doc.pointer(&path).unwrap(), node), "{}", path);
+    }
+    let numbers: Vec<(String, f64)> = doc
+        .walk()
+        .filter_map(|(path, node)| Some((path, node.content().as_f64()?)))
+        .collect();
+    assert_eq!(
+        numbers,
+        [
+            (String::from("/a/0"), 1.0),
+            (String::from("/c~1d/e~0f"), 2.5)
+        ]
+    );
+    assert_eq!(value("3").walk().count(), 1);
+}
+
+#[test]
+fn array_push_and_extend() {
+    let mut array = CargoArray::from(Vec::new());
+    array.push(CargoValue::number_i64(1));
+    array.push(CargoValue::string("two"));
+    array.push(value("{\"three\": [3]}"));
+    assert_eq!(array.len(), 3);
+    let doc = CargoValue::from(CargoContent::Array(Box::new(array)));
+    assert_eq!(doc.to_string(), "[1,\"two\",{\"three\":[3]}]");
+
+    let mut array = CargoArray::from(vec![CargoValue::null()]);
+    array.extend((1..=3).map(CargoValue::number_i64));
+    // A member of an object loses its name when it becomes an element.
+    let member = value("{\"a\": true}").content().get("a").unwrap().clone();
+    array.extend([member]);
+    assert_eq!(array[4].name(), None);
+    let doc = CargoValue::from(CargoContent::Array(Box::new(array)));
+    assert_eq!(doc.to_string(), "[null,1,2,3,true]");
+}
+
+#[test]
+fn object_insert_and_remove() {
+    let mut doc = value("{\"a\":1,\"b\":2}");
+    let object = doc.as_object_mut().unwrap();
+    assert_eq!(object.insert("c".to_string(), CargoValue::bool(true)), None);
+    let old = object.insert("a".to_string(), CargoValue::string("x"));
+    assert_eq!(old, Some(CargoValue::number_i64(1)));
+    assert_eq!(old.unwrap().name(), None);
+    assert_eq!(object.get("a").unwrap().name(), Some("a"));
+    assert_eq!(doc.to_string(), "{\"a\":\"x\",\"b\":2,\"c\":true}");
+    let object = doc.as_object_mut().unwrap();
+    assert_eq!(object.remove("b"), Some(CargoValue::number_i64(2)));
+    assert_eq!(object.remove("b"), None);
+    assert_eq!(object.len(), 2);
+    assert_eq!(doc.to_string(), "{\"a\":\"x\",\"c\":true}");
+    assert!(value("[]").as_object_mut().is_none());
+
+    // With duplicate names, insert replaces the last and remove removes them all.
+    let mut doc = value("{\"k\":1,\"m\":0,\"k\":2}");
+    let object = doc.as_object_mut().unwrap();
+    let old = object.insert("k".to_string(), CargoValue::number_i64(3));
+    assert_eq!(old, Some(CargoValue::number_i64(2)));
+    assert_eq!(doc.to_string(), "{\"k\":1,\"m\":0,\"k\":3}");
+    let object = doc.as_object_mut().unwrap();
+    assert_eq!(object.remove("k"), Some(CargoValue::number_i64(3)));
+    assert!(object.get("k").is_none());
+    assert_eq!(doc.to_string(), "{\"m\":0}");
+}
+
+#[test]
+fn try_from_converts_matching_values() {
+    let doc = value("{\"n\":-42,\"x\":2.5,\"i\":1.0,\"ok\":true,\"s\":\"caf\u{e9}\",\"z\":null}");
+    let n: i64 = doc.pointer("/n").unwrap().try_into().unwrap();
+    assert_eq!(n, -42);
+    assert_eq!(f64::try_from(doc.pointer("/n").unwrap()).unwrap(), -42.0);
+    assert_eq!(f64::try_from(doc.pointer("/x").unwrap()).unwrap(), 2.5);
+    assert!(bool::try_from(doc.pointer("/ok").unwrap()).unwrap());
+    assert_eq!(
+        String::try_from(doc.pointer("/s").unwrap()).unwrap(),
+        "caf\u{e9}"
+    );
+    for (pointer, err) in [
+        ("/x", i64::try_from(doc.pointer("/x").unwrap()).unwrap_err()),
+        ("/i", i64::try_from(doc.pointer("/i").unwrap()).unwrap_err()),
+        ("/s", i64::try_from(doc.pointer("/s").unwrap()).unwrap_err()),
+        (
+            "/z",
+            bool::try_from(doc.pointer("/z").unwrap()).unwrap_err(),
+        ),
+        (
+            "/n",
+            String::try_from(doc.pointer("/n").unwrap()).unwrap_err(),
+        ),
+        ("", f64::try_from(&doc).unwrap_err()),
+    ] {
+        assert!(
+            matches!(err, CargoError::TypeMismatch { .. }),
+            "{}: {}",
+            pointer,
+            err
+        );
+    }
+    assert_eq!(
+        i64::try_from(doc.pointer("/s").unwrap())
+            .unwrap_err()
+            .to_string(),
+        "error: expected integer, found string"
+    );
+    assert_eq!(
+        f64::try_from(&doc).unwrap_err().to_string(),
+        "error: expected number, found object"
+    );
+}
+
+#[test]
+fn into_accessors_move_the_content_out() {
+    let doc = value("{\"items\": [\"first\", 2, [true]], \"n\": 1.5}");
+    let mut object = doc.into_content().into_object().unwrap();
+    let items = object.get_mut("items").unwrap().take();
+    assert!(object.get("items").unwrap().is_null());
+    assert_eq!(object.get("items").unwrap().name(), Some("items"));
+    assert_eq!(items.name(), None);
+    let mut elements = items.into_content().into_array().unwrap();
+    drop(object);
+    // The elements outlive the value they came from, and the string is moved out
+    // of its element without being copied.
+    let first = elements.remove(0);
+    let buffer = first.content().as_str().unwrap().as_ptr();
+    let string = first.into_content().into_string().unwrap();
+    assert_eq!(string, "first");
+    assert_eq!(string.as_ptr(), buffer);
+    assert_eq!(elements.len(), 2);
+    let number = elements.remove(0).into_content().into_number().unwrap();
+    assert_eq!(number.as_i64(), Some(2));
+    assert_eq!(elements[0].to_string(), "[true]");
+    for (content, expected, found) in [
+        (
+            value("[]").into_content().into_object().map(drop),
+            "object",
+            "array",
+        ),
+        (
+            value("{}").into_content().into_array().map(drop),
+            "array",
+            "object",
+        ),
+        (
+            value("null").into_content().into_string().map(drop),
+            "string",
+            "null",
+        ),
+        (
+            value("\"1\"").into_content().into_number().map(drop),
+            "number",
+            "string",
+        ),
+    ] {
+        assert_eq!(
+            content.unwrap_err().to_string(),
+            format!("error: expected {}, found {}", expected, found)
+        );
+    }
+}
+
+#[test]
+fn diff_objects_by_name() {
+    let old = value("{\"keep\":1,\"gone\":true,\"n\":2}");
+    let new = value("{\"n\":3,\"keep\":1,\"new\":\"x\"}");
+    assert_eq!(
+        diff(&old, &new),
+        [
+            CargoDiff {
+                path: "/gone".into(),
+                kind: CargoDiffKind::Removed(value("true")),
+            },
+            CargoDiff {
+                path: "/n".into(),
+                kind: CargoDiffKind::Changed {
+                    old: value("2"),
+                    new: value("3"),
+                },
+            },
+            CargoDiff {
+                path: "/new".into(),
+                kind: CargoDiffKind::Added(value("\"x\"")),
+            },
+        ]
+    );
+    assert!(diff(&old, &old).is_empty());
+    assert!(diff(&value("{\"a\":1,\"b\":2}"), &value("{\"b\":2,\"a\":1}")).is_empty());
+    assert!(diff(&value("{\"a\":0,\"a\":1}"), &value("{\"a\":1}")).is_empty());
+}
+
+#[test]
+fn diff_arrays_and_nested_paths() {
+    let old = value("{\"a/b\":[1,[2,3],4],\"m~n\":{\"x\":null}}");
+    let new = value("{\"a/b\":[1,[2,5]],\"m~n\":{\"x\":false}}");
+    let diffs = diff(&old, &new);
+    let paths: Vec<&str> = diffs.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(paths, ["/a~1b/1/1", "/a~1b/2", "/m~0n/x"]);
+    assert_eq!(diffs[1].kind, CargoDiffKind::Removed(value("4")));
+    for d in &diffs {
+        assert!(old.pointer(&d.path).is_some());
+    }
+    let changed = diff(&value("[1]"), &value("{}"));
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].path, "");
+    assert_eq!(
+        diff(&value("[]"), &value("[0]"))[0].kind,
+        CargoDiffKind::Added(value("0"))
+    );
+}
+
+#[test]
+fn merge_patch_merges_objects_recursively() {
+    let mut target = value("{\"a\":1,\"b\":{\"c\":2,\"d\":3},\"e\":[1]}");
+    target.merge_patch(&value(
+        "{\"b\":{\"c\":4,\"f\":{\"g\":null,\"h\":5}},\"i\":true}",
+    ));
+    assert_eq!(
+        target.to_string(),
+        "{\"a\":1,\"b\":{\"c\":4,\"d\":3,\"f\":{\"h\":5}},\"e\":[1],\"i\":true}"
+    );
+}
+
+#[test]
+fn merge_patch_null_removes_members() {
+    let mut target = value("{\"a\":1,\"b\":2,\"a\":3}");
+    target.merge_patch(&value("{\"a\":null,\"missing\":null}"));
+    assert_eq!(target, value("{\"b\":2}"));
+    let mut target = value("{\"a\":{\"b\":1}}");
+    target.merge_patch(&value("{\"a\":{\"b\":null}}"));
+    assert_eq!(target, value("{\"a\":{}}"));
+}
+
+#[test]
+fn merge_patch_replaces_non_objects() {
+    let mut target = value("{\"a\":[1,2],\"b\":\"x\"}");
+    target.merge_patch(&value("{\"a\":[3],\"b\":{\"c\":1}}"));
+    assert_eq!(target, value("{\"a\":[3],\"b\":{\"c\":1}}"));
+    let mut target = value("{\"a\":1}");
+    target.merge_patch(&value("[\"whole\"]"));
+    assert_eq!(target, value("[\"whole\"]"));
+    let mut target = value("\"text\"");
+    target.merge_patch(&value("{\"a\":null,\"b\":2}"));
+    assert_eq!(target, value("{\"b\":2}"));
+}
+
+#[test]
+fn try_from_pairs_checks_names() {
+    let object = CargoObject::try_from_pairs(vec![
+        ("a".into(), CargoValue::number_i64(1)),
+        ("b".into(), CargoValue::bool(true)),
+    ])
+    .unwrap();
+    assert_eq!(object.len(), 2);
+    assert_eq!(object.get("b").unwrap().content().as_bool(), Some(true));
+    let err = CargoObject::try_from_pairs(vec![
+        ("a".into(), CargoValue::null()),
+        ("b".into(), CargoValue::null()),
+        ("a".into(), CargoValue::null()),
+    ])
+    .unwrap_err();
+    assert!(matches!(err, CargoError::DuplicateKey(name) if name == "a"));
+}
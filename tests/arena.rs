@@ -0,0 +1,77 @@
+#![cfg(feature = "bumpalo")]
+
+use rs_cargo::{parse, parse_in_arena, Arena, CargoBasic, CargoValueRef};
+use std::fs;
+
+#[test]
+fn arena_values_match_parse() {
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        let input = fs::read(&path).unwrap();
+        let arena = Arena::new();
+        let value = parse_in_arena(&input[..], &arena).unwrap();
+        let expected = parse(&input[..]).unwrap();
+        assert_eq!(value.to_value(), expected, "{}", path);
+        assert_eq!(
+            value.to_value().to_string(),
+            expected.to_string(),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn arena_values_borrow_from_the_arena() {
+    let arena = Arena::new();
+    let input = "{\"a\": [1, -0, 2.5e1, \"x\\u0101\"], \"b\": true}";
+    let CargoValueRef::Object(members) = parse_in_arena(input.as_bytes(), &arena).unwrap() else {
+        panic!("not an object");
+    };
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].0, "a");
+    let CargoValueRef::Array(elements) = members[0].1 else {
+        panic!("not an array");
+    };
+    assert_eq!(elements[0].as_i64(), Some(1));
+    assert_eq!(elements[1].as_f64(), Some(0.0));
+    assert_eq!(elements[2].as_i64(), None);
+    assert_eq!(elements[2].as_f64(), Some(25.0));
+    assert!(matches!(elements[3], CargoValueRef::String("x\u{101}")));
+    assert!(matches!(
+        members[1].1,
+        CargoValueRef::Basic(CargoBasic::CargoTrue(true))
+    ));
+}
+
+#[test]
+fn arena_reports_the_same_errors_as_parse() {
+    for input in [
+        "[1, 2",
+        "{\"a\" 1}",
+        "[]]",
+        "[+1]",
+        "{\"a\": tru}",
+        "\"\\x\"",
+    ] {
+        let arena = Arena::new();
+        assert_eq!(
+            parse_in_arena(input.as_bytes(), &arena)
+                .unwrap_err()
+                .to_string(),
+            parse(input.as_bytes()).unwrap_err().to_string(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn arena_can_be_reset_and_reused() {
+    let mut arena = Arena::new();
+    for _ in 0..3 {
+        let value = parse_in_arena("[\"a\", \"b\", {\"c\": null}]".as_bytes(), &arena).unwrap();
+        assert_eq!(value.to_value().to_string(), "[\"a\",\"b\",{\"c\":null}]");
+        arena.reset();
+    }
+}
@@ -0,0 +1,523 @@
+#![cfg(feature = "std")]
+
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{self, Command, Output, Stdio},
+};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rs-cargo"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn run_with_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rs-cargo"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The program may exit without reading its input (e.g. on bad arguments), so
+    // a broken pipe here is not an error.
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("rs-cargo-{}-{}", process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn reads_input_from_file() {
+    let path = temp_file("input.json", "[1, 2, 3]");
+    let output = run(&["-v", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn file_may_end_with_newlines_but_not_garbage() {
+    let path = temp_file("newlines.json", "{\"a\": 1}\r\n\r\n\n\n");
+    let output = run(&["-c", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}");
+    let path = temp_file("garbage.json", "{\"a\": 1}\n\n}\n");
+    let output = run(&["-v", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 3, column 1: unexpected character '}'\n  }\n  ^\n"
+    );
+}
+
+#[test]
+fn reports_file_that_cannot_be_opened() {
+    let path = env::temp_dir().join("rs-cargo-does-not-exist.json");
+    let output = run(&["-v", "-f", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(path.to_str().unwrap()));
+}
+
+#[test]
+fn canonicalizes_to_output_file() {
+    let path = temp_file("output.json", "stale contents that should be truncated");
+    let output = run_with_input(&["-c", "-o", path.to_str().unwrap()], "{ \"a\" : [1, 2] }");
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(written, "{\"a\":[1,2]}");
+}
+
+#[test]
+fn in_place_flag() {
+    let path = temp_file(
+        "in-place.json",
+        "{ \"b\" : [ 1.50, true ],\r\n  \"a\":\"x\" }\n",
+    );
+    let output = run(&["-c", "-i", "-f", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"b\":[0.15e1,true],\"a\":\"x\"}"
+    );
+    let output = run(&["--in-place", "-cp2", "-f", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\n  \"b\": [\n    0.15e1,\n    true\n  ],\n  \"a\": \"x\"\n}\n"
+    );
+    fs::remove_file(&path).unwrap();
+    // An invalid file is left as it was, and no temporary file is left beside it.
+    let messy = "[1, 2,\n 3 4]\n";
+    let path = temp_file("in-place-invalid.json", messy);
+    let output = run(&["-ci", "-f", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(fs::read_to_string(&path).unwrap(), messy);
+    let prefix = format!(".{}.", path.file_name().unwrap().to_str().unwrap());
+    let leftovers = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            name.to_str().is_some_and(|name| name.starts_with(&prefix))
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+    fs::remove_file(&path).unwrap();
+    for (args, message) in [
+        (
+            &["-c", "-i"][..],
+            "-i requires -f, as standard input cannot be replaced",
+        ),
+        (
+            &["-v", "-i", "-f", "x.json"],
+            "-i may only be given with -c",
+        ),
+        (
+            &["-c", "-i", "-f", "x.json", "-o", "y.json"],
+            "-i and -o cannot be given together",
+        ),
+        (
+            &["-c", "-i", "--measure", "-f", "x.json"],
+            "-i and --measure cannot be given together",
+        ),
+    ] {
+        let output = run_with_input(args, "[1]");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert_eq!(stderr.lines().next(), Some(message), "{:?}", args);
+    }
+}
+
+#[test]
+fn output_file_requires_canonicalize() {
+    let path = env::temp_dir().join(format!("rs-cargo-{}-unused.json", process::id()));
+    let output = run_with_input(&["-v", "-o", path.to_str().unwrap()], "[]");
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[-o FILE]"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn combined_short_flags() {
+    let pretty = |args: &[&str]| String::from_utf8(run_with_input(args, "[1]").stdout).unwrap();
+    assert_eq!(pretty(&["-cp"]), "[\n    1\n]\n");
+    assert_eq!(pretty(&["-cp", "2"]), "[\n  1\n]\n");
+    assert_eq!(pretty(&["-cp4"]), "[\n    1\n]\n");
+    assert_eq!(pretty(&["-pc"]), "[\n    1\n]\n");
+    let output = run_with_input(&["-cx"], "[1]");
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("[-h]"));
+}
+
+#[test]
+fn stderr_is_clean_on_success() {
+    let output = run_with_input(&["-c"], "{\"a\": true}");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(output.stdout, b"{\"a\":true}");
+}
+
+#[test]
+fn validate_exit_status() {
+    let output = run_with_input(&["-v"], "{\"a\": [1, 2.5, null]}\n");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let output = run_with_input(&["-v"], "{\"a\": [1, 2.5, null}\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn validate_writes_errors_only_to_stderr() {
+    let output = run_with_input(&["-v"], "{\"a\": [1, 2,]}");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 1, column 13"), "{}", stderr);
+    assert!(!stderr.contains("[-h]"));
+    let output = run_with_input(&["-v"], "{\"a\": [1, 2]}");
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn canonicalize_pretty_prints_with_indent() {
+    let input = fs::read_to_string("tests/test_inputs/simple_inp.json").unwrap();
+    let expected = fs::read_to_string("tests/test_outputs/exp_simple_out.json").unwrap();
+    let output = run_with_input(&["-c", "-p", "8"], &input);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn pretty_print_with_zero_indent() {
+    let output = run_with_input(&["-c", "-p", "0"], "{\"a\": [1, {}]}");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\n\"a\": [\n1,\n{\n}\n]\n}\n"
+    );
+}
+
+#[test]
+fn validate_rejects_empty_input() {
+    for input in ["", " \n\n "] {
+        let output = run_with_input(&["-v"], input);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("unexpected end of input"), "{}", stderr);
+    }
+}
+
+#[test]
+fn stats_are_printed_to_stderr() {
+    let input = "{\"a\": [1, 2.5, {\"b\": null}], \"c\": \"s\", \"d\": [true, false, []]}";
+    let expected = "objects: 2\narrays: 3\nstrings: 1\nnumbers: 2\nbooleans: 2\nnulls: 1\n\
+                    values: 11\nmax depth: 3\n";
+    let output = run_with_input(&["--stats"], input);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), expected);
+    let output = run_with_input(&["-c", "--stats"], input);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), expected);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"a\":[1,0.25e1,{\"b\":null}],\"c\":\"s\",\"d\":[true,false,[]]}"
+    );
+    let output = run_with_input(&["-v", "--stats"], "[1,");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("objects"));
+}
+
+#[test]
+fn exit_codes() {
+    assert_eq!(run(&["-h"]).status.code(), Some(0));
+    assert_eq!(run(&[]).status.code(), Some(2));
+    assert_eq!(run(&["-x"]).status.code(), Some(2));
+    assert_eq!(run_with_input(&["-v"], "[1]").status.code(), Some(0));
+    assert_eq!(run_with_input(&["-c"], "[1]").status.code(), Some(0));
+    assert_eq!(run_with_input(&["-v"], "[1,]").status.code(), Some(1));
+    assert_eq!(run_with_input(&["-c"], "tru").status.code(), Some(1));
+}
+
+#[test]
+fn validate_error_messages() {
+    for (input, expected) in [
+        (
+            "[1, 2",
+            "cargo: error at line 1, column 1: object or array opened here is never closed\n",
+        ),
+        (
+            "{\"a\" 1}",
+            "cargo: error at line 1, column 6: unexpected character '1'\n",
+        ),
+        (
+            "[\"a\\qb\"]",
+            "cargo: error at line 1, column 4: invalid escape sequence\n",
+        ),
+        (
+            "[1,\n -x]",
+            "cargo: error at line 2, column 2: invalid number\n",
+        ),
+        (
+            "{\"a\": tru}",
+            "cargo: error at line 1, column 10: unexpected character '}'\n",
+        ),
+    ] {
+        let output = run_with_input(&["-v"], input);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn max_precision_flag() {
+    let output = run_with_input(&["-c", "--max-precision", "3"], "[3.14159, 42]");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"[0.314e1,42]");
+    let output = run_with_input(&["--max-precision", "2", "-c"], "2.718");
+    assert_eq!(output.stdout, b"0.27e1");
+    for args in [
+        &["-c", "--max-precision"][..],
+        &["-c", "--max-precision", "x"],
+        &["-c", "--max-precision", "0"],
+        &["-c", "--max-precision", "18"],
+        &["-c", "--max-precision", "99"],
+        &["-v", "--max-precision", "3"],
+    ] {
+        let output = run_with_input(args, "1.5");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+    for (n, expected) in [("1", &b"0.3e1"[..]), ("17", b"0.31415926535897931e1")] {
+        let output = run_with_input(&["-c", "--max-precision", n], "3.141592653589793");
+        assert_eq!(output.stdout, expected, "{}", n);
+    }
+}
+
+#[test]
+fn arguments_are_validated_by_combination_not_count() {
+    let output = run_with_input(&["-c", "-p", "4"], "[1]");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"[\n    1\n]\n");
+    for args in [&["-v", "4"][..], &["-c", "4"], &["-v", "-p", "4"], &["4"]] {
+        let output = run_with_input(args, "[1]");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+    let path = temp_file("many-args.json", "[1.23456, {}]");
+    let output = run(&[
+        "-c",
+        "-p",
+        "2",
+        "-f",
+        path.to_str().unwrap(),
+        "--max-precision",
+        "2",
+        "--stats",
+    ]);
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"[\n  0.12e1,\n  {\n  }\n]\n");
+}
+
+#[test]
+fn file_errors_show_the_line_with_a_caret() {
+    let path = temp_file("caret.json", "{\n  \"a\": [1, 2],\n\t\"b\": tru\n}\n");
+    let output = run(&["-v", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 3, column 10: unexpected character '\\n'\n  \t\"b\": tru\n  \t        ^\n"
+    );
+    let path = temp_file("caret-eof.json", "[1,\r\n 2");
+    let output = run(&["-c", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 1, column 1: object or array opened here is never closed\n  [1,\n  ^\n"
+    );
+    // Errors in standard input are reported without the line.
+    let output = run_with_input(&["-v"], "[1 2]");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 1, column 4: unexpected character '2'\n"
+    );
+}
+
+#[test]
+fn no_final_newline_flag() {
+    let output = run_with_input(&["-c", "-p", "2", "--no-final-newline"], "[1]");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"[\n  1\n]");
+    let output = run_with_input(&["-c", "--no-final-newline"], "[1]");
+    assert_eq!(output.stdout, b"[1]");
+    let output = run_with_input(&["-c", "--stats", "-p", "--no-final-newline"], "[1]");
+    assert_eq!(output.stdout, b"[\n    1\n]");
+    let output = run_with_input(&["-c", "-p"], "[1]");
+    assert_eq!(output.stdout, b"[\n    1\n]\n");
+    let output = run_with_input(&["-v", "--no-final-newline"], "[1]");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn depth_flag() {
+    let two_deep = "{\"a\": [1, 2], \"b\": {}}";
+    let three_deep = "{\"a\": [1, {\"b\": 2}]}";
+    for mode in [&["-v"][..], &["-c"], &["-v", "--stats"], &["-c", "-p", "2"]] {
+        let args = [mode, &["--depth", "2"]].concat();
+        assert!(run_with_input(&args, two_deep).status.success());
+        let output = run_with_input(&args, three_deep);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with(
+                "cargo: error at line 1, column 11: objects and arrays nested too deeply\n"
+            ),
+            "{}",
+            stderr
+        );
+    }
+    let deep = "[".repeat(100_000) + &"]".repeat(100_000);
+    // The output goes to a file, since nothing reads standard output until all the
+    // input has been written.
+    let path = temp_file("deep.json", "");
+    let out = path.to_str().unwrap();
+    for args in [&["-v"][..], &["--stats"], &["-c", "-o", out]] {
+        let args = [args, &["--depth", "0"]].concat();
+        assert!(run_with_input(&args, &deep).status.success());
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), deep);
+    fs::remove_file(&path).unwrap();
+    assert!(run_with_input(&["-v", "--depth", "0"], "1")
+        .status
+        .success());
+    assert!(!run_with_input(&["-v", "--depth", "0"], "[")
+        .status
+        .success());
+    for bad in [
+        &["--depth"][..],
+        &["--depth", "-1"],
+        &["--depth", "01"],
+        &["--depth", "x"],
+    ] {
+        let args = [&["-v"][..], bad].concat();
+        assert_eq!(run_with_input(&args, "1").status.code(), Some(2));
+    }
+}
+
+#[test]
+fn select_flag() {
+    let input = "{\"users\": [{\"name\": \"ada\", \"tags\": [1, 2]}, {\"name\": \"bob\"}]}";
+    let output = run_with_input(&["--select", "/users/0/name"], input);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"\"ada\"");
+    let output = run_with_input(&["-c", "-p", "2", "--select", "/users/0"], input);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\n  \"name\": \"ada\",\n  \"tags\": [\n    1,\n    2\n  ]\n}\n"
+    );
+    let output = run_with_input(&["--select", "", "--stats"], input);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), canonical(input));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("objects: 3\n"));
+    for missing in ["/users/2", "/users/0/age", "users", "/users/x"] {
+        let output = run_with_input(&["--select", missing], input);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            format!("cargo: error: no value at {:?}\n", missing)
+        );
+    }
+    assert_eq!(run(&["--select"]).status.code(), Some(2));
+    assert_eq!(run(&["-v", "--select", "/users"]).status.code(), Some(2));
+}
+
+fn canonical(input: &str) -> String {
+    String::from_utf8(run_with_input(&["-c"], input).stdout).unwrap()
+}
+
+#[test]
+fn color_flag() {
+    let input = "{\"a\": [\"s\", 1.5, true, null]}";
+    let expected = "{\n  \x1b[1;34m\"a\"\x1b[0m: [\n    \x1b[32m\"s\"\x1b[0m,\n    \
+                    \x1b[36m0.15e1\x1b[0m,\n    \x1b[35mtrue\x1b[0m,\n    \x1b[35mnull\x1b[0m\n  ]\n}\n";
+    for args in [&["-c", "-p", "2"][..], &["-c", "-p", "2", "--stats"]] {
+        let output = run_with_input(&[args, &["--color", "always"]].concat(), input);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+        let plain = run_with_input(&[args, &["--color", "never"]].concat(), input);
+        assert!(!plain.stdout.contains(&b'\x1b'));
+        // Standard output is a pipe here, so auto leaves it uncolored.
+        assert_eq!(run_with_input(args, input).stdout, plain.stdout);
+    }
+    // Compact output is never colored.
+    let output = run_with_input(&["-c", "--color", "always"], input);
+    assert_eq!(output.stdout, b"{\"a\":[\"s\",0.15e1,true,null]}");
+    assert_eq!(run(&["-v", "--color", "never"]).status.code(), Some(2));
+    assert_eq!(run(&["-c", "--color", "sometimes"]).status.code(), Some(2));
+}
+
+#[test]
+fn measure_flag() {
+    let input = "{\"name\": \"rs-cargo\", \"tags\": [\"json\", 1.5], \"empty\": {}}";
+    let output = run_with_input(&["-c", "-p", "--measure"], input);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"lines: 9\nmax width: 23\n");
+    let output = run_with_input(&["-c", "-p", "2", "--measure", "--color", "always"], input);
+    assert_eq!(output.stdout, b"lines: 9\nmax width: 21\n");
+    let output = run_with_input(&["-c", "--measure", "--select", "/tags"], input);
+    assert_eq!(output.stdout, b"lines: 1\nmax width: 15\n");
+    assert_eq!(run(&["-v", "--measure"]).status.code(), Some(2));
+}
+
+#[test]
+fn report_all_flag() {
+    let input = "[1 2,\n {\"a\" 3}]\n";
+    let output = run_with_input(&["--report-all"], input);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 1, column 4: unexpected character '2'\n\
+         cargo: error at line 2, column 7: unexpected character '3'\n"
+    );
+    let path = temp_file("report-all.json", input);
+    let output = run(&["-v", "--report-all", "-f", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cargo: error at line 1, column 4: unexpected character '2'\n  [1 2,\n     ^\n\
+         cargo: error at line 2, column 7: unexpected character '3'\n   {\"a\" 3}]\n        ^\n"
+    );
+    let output = run_with_input(&["--report-all"], "[1, 2]");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(run(&["-c", "--report-all"]).status.code(), Some(2));
+    assert_eq!(run(&["--stats", "--report-all"]).status.code(), Some(2));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "bigdecimal")]
+
+use rs_cargo::{canonicalize_str, parse_str, BigDecimal, CargoContent, CargoNumber, WriteOpts};
+use std::str::FromStr;
+
+const PI: &str = "3.141592653589793238462643383279502884197";
+
+#[test]
+fn long_decimals_are_written_exactly() {
+    for (input, expected) in [
+        (PI, "0.3141592653589793238462643383279502884197e1"),
+        (
+            "-0.0000123456789012345678901234567890123456789E-3",
+            "-0.123456789012345678901234567890123456789e-7",
+        ),
+        (
+            "12345678901234567890123456789012345678901",
+            "0.12345678901234567890123456789012345678901e41",
+        ),
+        ("1e-400", "0.1e-399"),
+        ("2.50", "0.25e1"),
+        ("-0.000", "-0.0"),
+        ("42", "42"),
+    ] {
+        assert_eq!(canonicalize_str(input).unwrap(), expected, "{}", input);
+        assert_eq!(canonicalize_str(expected).unwrap(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn decimal_value_is_exact() {
+    let CargoContent::Number(number) = parse_str(PI).unwrap().into_content() else {
+        panic!("not a number");
+    };
+    assert_eq!(
+        number.as_decimal(),
+        Some(&BigDecimal::from_str(PI).unwrap())
+    );
+    assert_eq!(
+        CargoNumber::from(7).as_decimal(),
+        Some(&BigDecimal::from(7))
+    );
+    assert_eq!(CargoNumber::try_from(0.5).unwrap().as_decimal(), None);
+}
+
+#[test]
+fn precision_rounds_decimals() {
+    let opts = WriteOpts {
+        precision: Some(5),
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    rs_cargo::write_cargo_value(&mut out, &parse_str(PI).unwrap(), &opts).unwrap();
+    assert_eq!(out, "0.31416e1");
+}
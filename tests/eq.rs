@@ -0,0 +1,219 @@
+use rs_cargo::{CargoContent, CargoNumber, CargoString, CargoValue};
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BTreeSet, HashSet},
+    hash::{Hash, Hasher},
+};
+
+fn float(f: f64) -> CargoNumber {
+    CargoNumber::try_from(f).unwrap()
+}
+
+fn value(s: &str) -> CargoValue {
+    s.parse().unwrap()
+}
+
+#[test]
+fn scalars_compare_by_value() {
+    assert_eq!(value("\"a\\u0062\""), value("\"ab\""));
+    assert_ne!(value("\"ab\""), value("\"ba\""));
+    assert_eq!(value("true"), value("true"));
+    assert_ne!(value("true"), value("false"));
+    assert_eq!(value("null"), value(" null "));
+    assert_ne!(value("null"), value("0"));
+    assert_ne!(value("\"1\""), value("1"));
+}
+
+#[test]
+fn numbers_compare_by_value() {
+    assert_eq!(value("1"), value("1.0"));
+    assert_eq!(value("1"), value("0.1e1"));
+    assert_eq!(value("-2.50"), value("-25E-1"));
+    assert_ne!(value("1"), value("1.5"));
+    assert_eq!(CargoNumber::from(3), float(3.0));
+    assert_ne!(
+        value("9007199254740993"),
+        CargoValue::from(CargoContent::Number(CargoNumber::from(9007199254740992)))
+    );
+    assert_eq!(
+        value("123456789012345678901234567890"),
+        value("1.2345678901234568e29")
+    );
+}
+
+#[test]
+fn ints_and_floats_compare_exactly_beyond_2_pow_53() {
+    let two_53 = 1i64 << 53;
+    let int_above = CargoNumber::from(two_53 + 1);
+    let float_53 = float(two_53 as f64);
+    let int_53 = CargoNumber::from(two_53);
+    assert_eq!(float_53, int_53);
+    assert_ne!(int_above, float_53);
+    assert_ne!(float_53, int_above);
+    assert_eq!(float_53.cmp(&int_above), Ordering::Less);
+    assert_eq!(int_above.cmp(&float_53), Ordering::Greater);
+    assert!(float(two_53 as f64 + 2.0) > int_above);
+    assert!(CargoNumber::from(i64::MAX) < float(i64::MAX as f64));
+    assert!(CargoNumber::from(i64::MIN) == float(i64::MIN as f64));
+    assert!(CargoNumber::from(-two_53 - 1) < float(-two_53 as f64));
+    assert!(CargoNumber::from(-3) < float(-2.5));
+    assert!(CargoNumber::from(-2) > float(-2.5));
+
+    let mut numbers: Vec<CargoNumber> = (-3..=3)
+        .flat_map(|d| {
+            let n = two_53 + d;
+            [CargoNumber::from(n), float(n as f64)]
+        })
+        .collect();
+    numbers.sort();
+    for pair in numbers.windows(2) {
+        assert!(pair[0] <= pair[1], "{:?}", pair);
+    }
+    // Each float equals an int except that 2^53 + 3 rounds to the float 2^53 + 4.
+    let distinct: BTreeSet<CargoNumber> = numbers.into_iter().collect();
+    assert_eq!(distinct.len(), 8, "{:?}", distinct);
+    assert_eq!(
+        distinct.last().and_then(CargoNumber::as_f64),
+        Some(two_53 as f64 + 4.0)
+    );
+}
+
+#[test]
+fn numbers_sort_by_value() {
+    let mut numbers = vec![
+        CargoNumber::from(2),
+        float(1.5),
+        CargoNumber::from(-3),
+        float(-0.0),
+        CargoNumber::from(i64::MAX),
+        float(-2.75),
+        float(1e300),
+        CargoNumber::from(1),
+        CargoNumber::from(i64::MIN),
+    ];
+    numbers.sort();
+    let sorted: Vec<f64> = numbers.iter().filter_map(CargoNumber::as_f64).collect();
+    assert_eq!(
+        sorted,
+        [
+            -9.223372036854776e18,
+            -3.0,
+            -2.75,
+            -0.0,
+            1.0,
+            1.5,
+            2.0,
+            9.223372036854776e18,
+            1e300
+        ]
+    );
+    assert!(CargoNumber::from(2) > float(1.5));
+    assert!(CargoNumber::from(i64::MAX - 1) < CargoNumber::from(i64::MAX));
+    assert_eq!(CargoNumber::from(0).cmp(&float(-0.0)), Ordering::Equal);
+    assert_eq!(
+        CargoNumber::from(1).partial_cmp(&float(1.0)),
+        Some(Ordering::Equal)
+    );
+}
+
+#[test]
+fn arrays_are_ordered() {
+    assert_eq!(value("[1, [2, \"x\"]]"), value("[1.0,[2,\"x\"]]"));
+    assert_ne!(value("[1, 2]"), value("[2, 1]"));
+    assert_ne!(value("[1, 2]"), value("[1, 2, 3]"));
+    assert_ne!(value("[]"), value("{}"));
+}
+
+#[test]
+fn objects_are_unordered() {
+    assert_eq!(
+        value("{\"a\": 1, \"b\": [true]}"),
+        value("{\"b\": [true], \"a\": 1}")
+    );
+    assert_ne!(value("{\"a\": 1}"), value("{\"a\": 2}"));
+    assert_ne!(value("{\"a\": 1}"), value("{\"b\": 1}"));
+    assert_ne!(value("{\"a\": 1}"), value("{\"a\": 1, \"b\": 2}"));
+    assert_eq!(value("{\"a\": 1, \"a\": 2}"), value("{\"a\": 2}"));
+    assert_eq!(
+        value("{\"x\": {\"p\": 1, \"q\": 2}}"),
+        value("{\"x\": {\"q\": 2, \"p\": 1}}")
+    );
+}
+
+#[test]
+fn strings_ignore_capacity() {
+    let mut s = String::with_capacity(100);
+    s.push_str("cargo");
+    assert_eq!(
+        CargoString::from(s),
+        CargoString::from(String::from("cargo"))
+    );
+}
+
+fn hash_of(value: &CargoValue) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn hash_agrees_with_eq() {
+    let mut set = HashSet::new();
+    assert!(set.insert(value(
+        "{\"a\":1,\"b\":[true,null],\"c\":{\"x\":\"y\",\"z\":2}}"
+    )));
+    assert!(!set.insert(value(
+        "{\"c\":{\"z\":2,\"x\":\"y\"},\"b\":[true,null],\"a\":1}"
+    )));
+    assert!(!set.insert(value(
+        "{\"b\":[true,null],\"a\":1.0,\"c\":{\"x\":\"y\",\"z\":0.2e1}}"
+    )));
+    assert!(set.insert(value(
+        "{\"a\":1,\"b\":[null,true],\"c\":{\"x\":\"y\",\"z\":2}}"
+    )));
+    assert_eq!(set.len(), 2);
+    for (a, b) in [
+        ("1", "1.0"),
+        ("100", "1e2"),
+        ("0", "-0.0"),
+        ("{\"a\":1,\"a\":2}", "{\"a\":2}"),
+        ("{\"a\":1,\"b\":2,\"a\":3}", "{\"b\":2,\"a\":3}"),
+        ("\"a\\u0062\"", "\"ab\""),
+    ] {
+        assert_eq!(value(a), value(b), "{} {}", a, b);
+        assert_eq!(hash_of(&value(a)), hash_of(&value(b)), "{} {}", a, b);
+    }
+    let deduplicated: HashSet<CargoValue> = value("[1, \"1\", 1.0, [1], [1.0], {}, {}, null]")
+        .content()
+        .as_array()
+        .unwrap()
+        .iter()
+        .cloned()
+        .collect();
+    assert_eq!(deduplicated.len(), 5);
+}
+
+#[test]
+fn canonical_hash_depends_only_on_canonical_form() {
+    let compact = value("{\"a\":[1,2.5,\"x\\u00e9\"],\"b\":{\"c\":null}}");
+    for formatted in [
+        "{ \"a\" : [ 1 , 2.5 , \"x\u{e9}\" ] ,\n  \"b\" : { \"c\" : null } }",
+        "{\"a\":[1,0.25e1,\"x\\u00e9\"],\"b\":{\"c\":null}}",
+        "\t{\"a\":[1,25e-1,\"\\u0078\\u00E9\"],\"b\":{\"c\"\n:null}}\n",
+    ] {
+        let value = value(formatted);
+        assert_eq!(
+            value.canonical_hash(),
+            compact.canonical_hash(),
+            "{}",
+            formatted
+        );
+    }
+    assert_ne!(
+        value("{\"b\":{\"c\":null},\"a\":[1,2.5,\"x\u{e9}\"]}").canonical_hash(),
+        compact.canonical_hash()
+    );
+    assert_ne!(value("[1]").canonical_hash(), value("[2]").canonical_hash());
+    // The hash is FNV-1a of the canonical text, so it is fixed for all time.
+    assert_eq!(value("null").canonical_hash(), 0x5b9b_c4ba_5281_08e4);
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "std")]
+
+use rs_cargo::{
+    for_each_element, parse, parse_events, CargoError, CargoEvent, CargoReader, ParseConfig,
+};
+use std::{fs::File, io};
+
+fn events(input: &str) -> Result<Vec<CargoEvent>, CargoError> {
+    let mut events = Vec::new();
+    parse_events(input.as_bytes(), |event| {
+        events.push(event);
+        Ok(())
+    })?;
+    Ok(events)
+}
+
+#[test]
+fn count_events_for_nested_document() {
+    let events =
+        events("{\"a\": [1, {\"b\": null}, []], \"c\": {\"d\": \"x\", \"e\": true}}").unwrap();
+    assert_eq!(events.len(), 19);
+    let count = |f: fn(&CargoEvent) -> bool| events.iter().filter(|e| f(e)).count();
+    assert_eq!(count(|e| matches!(e, CargoEvent::StartObject)), 3);
+    assert_eq!(count(|e| matches!(e, CargoEvent::EndObject)), 3);
+    assert_eq!(count(|e| matches!(e, CargoEvent::StartArray)), 2);
+    assert_eq!(count(|e| matches!(e, CargoEvent::EndArray)), 2);
+    assert_eq!(count(|e| matches!(e, CargoEvent::Key(_))), 5);
+    assert!(matches!(&events[1], CargoEvent::Key(key) if key == "a"));
+    assert!(matches!(&events[3], CargoEvent::Number(n) if n.as_i64() == Some(1)));
+    assert!(matches!(&events[6], CargoEvent::Null));
+    assert!(matches!(&events[14], CargoEvent::Str(s) if s == "x"));
+    assert!(matches!(&events[16], CargoEvent::Bool(true)));
+}
+
+#[test]
+fn events_for_top_level_scalars() {
+    assert!(matches!(&events(" \"s\" ").unwrap()[..], [CargoEvent::Str(s)] if s == "s"));
+    assert!(matches!(
+        &events("false").unwrap()[..],
+        [CargoEvent::Bool(false)]
+    ));
+    assert!(matches!(
+        &events("[]").unwrap()[..],
+        [CargoEvent::StartArray, CargoEvent::EndArray]
+    ));
+}
+
+#[test]
+fn events_accept_test_inputs() {
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        assert!(
+            parse_events(File::open(&path).unwrap(), |_| Ok(())).is_ok(),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn events_report_the_same_errors_as_parse() {
+    for input in [
+        "[1, 2",
+        "{\"a\" 1}",
+        "[]]",
+        "{\"a\":1,}",
+        "[1,]",
+        "{1:2}",
+        "[1 2]",
+        "{\"a\":1]",
+        "[NaN]",
+        "",
+    ] {
+        let expected = parse(input.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            events(input).unwrap_err().to_string(),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn callback_error_stops_reading() {
+    let mut seen = 0;
+    let err = parse_events(&b"[1, 2, 3]"[..], |event| {
+        seen += 1;
+        match event {
+            CargoEvent::Number(_) => Err(CargoError::InvalidNumber { at: (0, 0) }),
+            _ => Ok(()),
+        }
+    })
+    .unwrap_err();
+    assert!(matches!(err, CargoError::InvalidNumber { at: (0, 0) }));
+    assert_eq!(seen, 2);
+}
+
+#[test]
+fn events_allow_trailing_commas_in_relaxed_mode() {
+    let config = ParseConfig {
+        relaxed: true,
+        ..ParseConfig::default()
+    };
+    let mut reader = CargoReader::with_config(&b"{\"a\":[1,],}"[..], config);
+    let mut seen = Vec::new();
+    rs_cargo::cargo::read_cargo_events(&mut reader, &mut |event| {
+        seen.push(event);
+        Ok(())
+    })
+    .unwrap();
+    assert!(matches!(
+        &seen[..],
+        [
+            CargoEvent::StartObject,
+            CargoEvent::Key(_),
+            CargoEvent::StartArray,
+            CargoEvent::Number(_),
+            CargoEvent::EndArray,
+            CargoEvent::EndObject
+        ]
+    ));
+    assert!(events("{\"a\":[1,],}").is_err());
+}
+
+/*
+ * Generates the array [{"id": 0, "size": 0}, {"id": 1, "size": 2}, ...] of
+ * "count" records as it is read, so that the whole input never exists at once.
+ */
+struct Records {
+    next: usize,
+    count: usize,
+    pending: Vec<u8>,
+}
+
+impl io::Read for Records {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() && self.next <= self.count {
+            let text = match self.next {
+                0 => String::from("["),
+                n if n == self.count => String::from("]"),
+                n => format!(
+                    "{}{{\"id\": {}, \"size\": {}}}",
+                    if n > 1 { "," } else { "" },
+                    n - 1,
+                    2 * (n - 1)
+                ),
+            };
+            self.pending = text.into_bytes();
+            self.next += 1;
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn for_each_element_streams_an_array() {
+    let count = 200_000;
+    let records = Records {
+        next: 0,
+        count: count + 1,
+        pending: Vec::new(),
+    };
+    let mut total = 0;
+    let mut seen = 0;
+    for_each_element(records, |record| {
+        total += record
+            .content()
+            .get("size")
+            .unwrap()
+            .content()
+            .as_i64()
+            .unwrap();
+        seen += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(seen, count);
+    assert_eq!(total, (count * (count - 1)) as i64);
+
+    let mut elements = Vec::new();
+    for_each_element(&b" [1, [2], {\"a\": 3}] "[..], |element| {
+        elements.push(element.to_string());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(elements, ["1", "[2]", "{\"a\":3}"]);
+    assert!(for_each_element(&b"[]"[..], |_| panic!()).is_ok());
+    // The callback can stop the reading early.
+    let mut calls = 0;
+    let err = for_each_element(&b"[1, 2, 3]"[..], |_| {
+        calls += 1;
+        Err(CargoError::TypeMismatch {
+            expected: "string",
+            found: "number",
+        })
+    })
+    .unwrap_err();
+    assert!(matches!(err, CargoError::TypeMismatch { .. }));
+    assert_eq!(calls, 1);
+    for (input, expected) in [
+        ("{\"a\": 1}", "unexpected character '{'"),
+        ("1", "unexpected character '1'"),
+        ("[1, 2", "object or array opened here is never closed"),
+        ("[1] 2", "unexpected character '2'"),
+        ("[1,]", "unexpected character ']'"),
+    ] {
+        let err = for_each_element(input.as_bytes(), |_| Ok(())).unwrap_err();
+        assert!(err.to_string().ends_with(expected), "{}: {}", input, err);
+    }
+}
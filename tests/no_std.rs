@@ -0,0 +1,35 @@
+// These tests use only what is available without the "std" feature, so they can
+// be run with --no-default-features --features alloc as well as by default.
+use rs_cargo::{canonicalize_str, parse_slice, CargoError};
+
+#[test]
+fn parse_slice_reads_values() {
+    let value = parse_slice(b" {\"a\": [1, -2.5e3, \"caf\xc3\xa9\", true, null]} ").unwrap();
+    let elements = value
+        .content()
+        .get("a")
+        .unwrap()
+        .content()
+        .as_array()
+        .unwrap();
+    assert_eq!(elements[0].content().as_i64(), Some(1));
+    assert_eq!(elements[2].content().as_str(), Some("café"));
+    assert_eq!(
+        value.to_string(),
+        "{\"a\":[1,-0.25e4,\"caf\u{e9}\",true,null]}"
+    );
+    assert_eq!(canonicalize_str("[ 1 , {} ]").unwrap(), "[1,{}]");
+}
+
+#[test]
+fn parse_slice_reports_errors() {
+    assert!(matches!(
+        parse_slice(b"[1, 2").unwrap_err(),
+        CargoError::UnterminatedContainer { opened_at: (1, 1) }
+    ));
+    assert!(matches!(
+        parse_slice(b"\"\xff\"").unwrap_err(),
+        CargoError::InvalidUtf8 { at: (1, 2) }
+    ));
+    assert!(parse_slice(b"[1] 2").is_err());
+}
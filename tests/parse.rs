@@ -0,0 +1,1048 @@
+#![cfg(feature = "std")]
+
+use rs_cargo::{
+    canonicalize_str,
+    cargo::{read_cargo_number, CARGO_MAX_DEPTH, CARGO_MAX_LOOKAHEAD},
+    from_reader_with_config, is_valid, parse, parse_many, parse_str, tokenize, validate_all,
+    validate_only, CargoBasic, CargoContent, CargoError, CargoRead, CargoReader, CargoSpan,
+    CargoStrReader, CargoToken, CargoValue, ParseConfig,
+};
+use std::{fs::File, io};
+
+#[test]
+fn parse_from_byte_slice() {
+    let input: &[u8] = b" {\"a\": [1, -2.5e3, \"x\\n\", true, false, null], \"b\": {}} ";
+    let value = parse(input).unwrap();
+    assert!(matches!(value.content(), CargoContent::Object(_)));
+}
+
+#[test]
+fn parse_test_inputs() {
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        assert!(parse(File::open(&path).unwrap()).is_ok(), "{}", path);
+    }
+}
+
+#[test]
+fn parse_rejects_malformed_input() {
+    let err = parse(&b"[1, 2"[..]).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnterminatedContainer { opened_at: (1, 1) }
+    ));
+    let err = parse(&b"{\"a\" 1}"[..]).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: '1',
+            at: (1, 6)
+        }
+    ));
+    let err = parse(&b"[]]"[..]).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: ']',
+            at: (1, 3)
+        }
+    ));
+}
+
+#[test]
+fn from_str_parses_values() {
+    let value: CargoValue = "true".parse().unwrap();
+    assert!(matches!(
+        value.content(),
+        CargoContent::Basic(CargoBasic::CargoTrue(true))
+    ));
+    let value: CargoValue = "[1,2,3]".parse().unwrap();
+    assert_eq!(value.to_string(), "[1,2,3]");
+    assert!("[1,2,".parse::<CargoValue>().is_err());
+}
+
+#[test]
+fn rejects_non_finite_numbers() {
+    for input in [
+        "NaN",
+        "Infinity",
+        "-Infinity",
+        "1e400",
+        "-1e400",
+        "[1, NaN]",
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidNumber { .. }),
+            "{}: {}",
+            input,
+            err
+        );
+    }
+    assert!(parse(&b"1e300"[..]).is_ok());
+}
+
+#[test]
+fn duplicate_keys_last_wins_by_default() {
+    let value = parse(&b"{\"a\":1,\"a\":2}"[..]).unwrap();
+    assert_eq!(
+        value.content().get("a").unwrap().content().as_i64(),
+        Some(2)
+    );
+}
+
+#[test]
+fn duplicate_keys_rejected_when_configured() {
+    let config = ParseConfig {
+        reject_duplicate_keys: true,
+        ..ParseConfig::default()
+    };
+    let err =
+        from_reader_with_config(&b"{\"a\":1,\"b\":{\"a\":2},\"a\":3}"[..], &config).unwrap_err();
+    assert!(matches!(err, CargoError::DuplicateKey(ref name) if name == "a"));
+    assert!(from_reader_with_config(&b"{\"a\":1,\"b\":{\"a\":2}}"[..], &config).is_ok());
+}
+
+#[test]
+fn parse_multibyte_strings() {
+    for text in ["café", "日本語", "😀 ok"] {
+        let input = format!("[\"{}\"]", text);
+        let value = parse(input.as_bytes()).unwrap();
+        let elements = value.content().as_array().unwrap();
+        assert_eq!(elements[0].content().as_str(), Some(text));
+    }
+    // Columns count characters rather than bytes.
+    let err = parse("[\"日本語\" 1]".as_bytes()).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: '1',
+            at: (1, 8)
+        }
+    ));
+}
+
+#[test]
+fn lone_surrogate_escapes_are_rejected() {
+    // A string holds Unicode scalar values, which a surrogate on its own is not.
+    for (input, at) in [
+        ("\"\\uddab\"", (1, 2)),
+        ("[\"a\\ud83d\"]", (1, 4)),
+        ("\"\\ud83d\\u0041\"", (1, 2)),
+        ("\"\\uDE00\\ud83d\"", (1, 2)),
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidEscape { at: a } if a == at),
+            "{}: {:?}",
+            input,
+            err
+        );
+    }
+    let value = parse(&b"\"\\uD83D\\uDDAB\""[..]).unwrap();
+    assert_eq!(value.content().as_str(), Some("\u{1f5ab}"));
+}
+
+#[test]
+fn parse_rejects_invalid_utf8() {
+    for input in [
+        &b"\"caf\xe9\""[..],
+        &b"\"\xff\""[..],
+        &b"\"\x80\""[..],
+        &b"\"\xe6\x97\""[..],
+        &b"\"\xc0\xaf\""[..],
+        &b"\"\xed\xa0\x80\""[..],
+        &b"\"\xe6\x97"[..],
+    ] {
+        let err = parse(input).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidUtf8 { .. }),
+            "{:?}: {}",
+            input,
+            err
+        );
+    }
+    assert!(matches!(
+        parse(&b"[\"ab\xff\"]"[..]).unwrap_err(),
+        CargoError::InvalidUtf8 { at: (1, 5) }
+    ));
+}
+
+#[test]
+fn validate_only_agrees_with_parse() {
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        assert!(
+            validate_only(File::open(&path).unwrap()).is_ok(),
+            "{}",
+            path
+        );
+    }
+    for input in ["[1, 2", "{\"a\" 1}", "[]]", "\"\\x\"", "01"] {
+        let expected = parse(input.as_bytes()).unwrap_err().to_string();
+        let err = validate_only(input.as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn raw_control_characters_in_strings() {
+    let err = parse(&b"[\"a\tb\"]"[..]).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: '\t',
+            at: (1, 4)
+        }
+    ));
+    let err = parse(&b"\"a\nb\""[..]).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar { found: '\n', .. }
+    ));
+    assert!(parse(&b"\"\x1f\""[..]).is_err());
+    let value = parse(&b"\"a\\tb\\nc\""[..]).unwrap();
+    assert_eq!(value.content().as_str(), Some("a\tb\nc"));
+    let value = parse(&b"\"\x7f\""[..]).unwrap();
+    assert_eq!(value.content().as_str(), Some("\u{7f}"));
+}
+
+#[test]
+fn trailing_commas_only_in_relaxed_mode() {
+    let relaxed = ParseConfig {
+        relaxed: true,
+        ..ParseConfig::default()
+    };
+    for (input, canonical) in [
+        ("[1,2,]", "[1,2]"),
+        ("{\"a\":1,}", "{\"a\":1}"),
+        (
+            "{\"a\":[1 , ] , \"b\":{\"c\":null,\n}\t,\n}",
+            "{\"a\":[1],\"b\":{\"c\":null}}",
+        ),
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::UnexpectedChar { .. }),
+            "{}: {}",
+            input,
+            err
+        );
+        let value = from_reader_with_config(input.as_bytes(), &relaxed).unwrap();
+        assert_eq!(value.to_string(), canonical);
+    }
+    for input in ["[,]", "[1,,]", "{,}", "[1,2,,]"] {
+        assert!(
+            from_reader_with_config(input.as_bytes(), &relaxed).is_err(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn comments_only_in_relaxed_mode() {
+    let relaxed = ParseConfig {
+        relaxed: true,
+        ..ParseConfig::default()
+    };
+    let input = "// settings\n{\n  \"name\": \"cargo\", // the name\n  /* the\n   * version */ \"version\": /**/ 2\n}\n/* end **/";
+    let err = parse(input.as_bytes()).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: '/',
+            at: (1, 1)
+        }
+    ));
+    let value = from_reader_with_config(input.as_bytes(), &relaxed).unwrap();
+    assert_eq!(value.to_string(), "{\"name\":\"cargo\",\"version\":2}");
+    assert!(from_reader_with_config(&b"[1] // no newline"[..], &relaxed).is_ok());
+    assert!(from_reader_with_config(&b"[\"/* not a comment */\"]"[..], &relaxed).is_ok());
+    let err = from_reader_with_config(&b"[1 /* open"[..], &relaxed).unwrap_err();
+    assert!(matches!(err, CargoError::UnexpectedEof { at: (1, 11) }));
+    let err = from_reader_with_config(&b"[1 / 2]"[..], &relaxed).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: ' ',
+            at: (1, 5)
+        }
+    ));
+}
+
+#[test]
+fn trailing_whitespace_is_accepted_but_not_anything_else() {
+    for input in ["{}\n\n\n", "[1]\r\n\r\n", "true \t \n \r\n", "\"x\"\r"] {
+        assert!(parse(input.as_bytes()).is_ok(), "{:?}", input);
+        assert!(parse_str(input).is_ok(), "{:?}", input);
+        assert!(validate_only(input.as_bytes()).is_ok(), "{:?}", input);
+        assert!(is_valid(input), "{:?}", input);
+    }
+    for (input, found, at) in [
+        ("{}\n\n}", '}', (3, 1)),
+        ("[1]\r\n[2]\r\n", '[', (2, 1)),
+        ("1\n\n  x", 'x', (3, 3)),
+        ("null\u{feff}", '\u{feff}', (1, 5)),
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::UnexpectedChar { found: f, at: a } if f == found && a == at),
+            "{:?}: {:?}",
+            input,
+            err
+        );
+        let err = validate_only(input.as_bytes()).unwrap_err();
+        assert_eq!(err.position(), Some(at), "{:?}", input);
+        assert!(!is_valid(input), "{:?}", input);
+    }
+}
+
+#[test]
+fn byte_order_mark_at_start() {
+    let relaxed = ParseConfig {
+        relaxed: true,
+        ..ParseConfig::default()
+    };
+    let input = b"\xef\xbb\xbf{\"a\": [1, 2]}";
+    let err = parse(&input[..]).unwrap_err();
+    assert!(matches!(err, CargoError::ByteOrderMark));
+    let value = from_reader_with_config(&input[..], &relaxed).unwrap();
+    assert_eq!(value.to_string(), "{\"a\":[1,2]}");
+    assert!(matches!(
+        parse(&b"\xef\xbb\xbf"[..]).unwrap_err(),
+        CargoError::ByteOrderMark
+    ));
+    assert!(matches!(
+        from_reader_with_config(&b"\xef\xbb\xbf"[..], &relaxed).unwrap_err(),
+        CargoError::UnexpectedEof { .. }
+    ));
+    // Only a byte order mark at the very start is treated specially.
+    let err = from_reader_with_config(&b" \xef\xbb\xbf1"[..], &relaxed).unwrap_err();
+    assert!(matches!(
+        err,
+        CargoError::UnexpectedChar {
+            found: '\u{feff}',
+            ..
+        }
+    ));
+}
+
+#[test]
+fn empty_input_is_rejected() {
+    for (input, at) in [("", (1, 1)), ("   ", (1, 4)), (" \n\t\r\n  ", (3, 3))] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::UnexpectedEof { at: pos } if pos == at),
+            "{:?}: {}",
+            input,
+            err
+        );
+        assert!(validate_only(input.as_bytes()).is_err());
+    }
+    assert_eq!(
+        parse(&b""[..]).unwrap_err().to_string(),
+        "error at line 1, column 1: unexpected end of input"
+    );
+}
+
+#[test]
+fn exponent_needs_digits_after_optional_sign() {
+    for (input, expected) in [
+        ("1e5", 100000.0),
+        ("1E5", 100000.0),
+        ("1e+5", 100000.0),
+        ("1E+5", 100000.0),
+        ("1e-5", 0.00001),
+        ("-2.5E-05", -0.000025),
+        ("1e0", 1.0),
+    ] {
+        let value = parse(input.as_bytes()).unwrap();
+        assert_eq!(value.content().as_f64(), Some(expected), "{}", input);
+        assert_eq!(value.content().as_i64(), None, "{}", input);
+        assert!(validate_only(input.as_bytes()).is_ok(), "{}", input);
+    }
+    for input in [
+        "1e", "1E", "1e+", "1e-", "1E+", "1e+-5", "1e++5", "1ex", "1e.5", "[1e]",
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidNumber { .. }),
+            "{}: {:?}",
+            input,
+            err
+        );
+        assert!(validate_only(input.as_bytes()).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn number_needs_a_digit_after_a_single_minus() {
+    for input in [
+        "-",
+        "--1",
+        "+1",
+        "-.5",
+        "+",
+        "-+1",
+        "- 1",
+        "[+1]",
+        "{\"a\": -}",
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidNumber { .. }),
+            "{}: {:?}",
+            input,
+            err
+        );
+        let err = validate_only(input.as_bytes()).unwrap_err();
+        assert!(matches!(err, CargoError::InvalidNumber { .. }), "{}", input);
+    }
+    for (input, expected) in [("-1", -1.0), ("-0.5", -0.5), ("-10", -10.0)] {
+        let value = parse(input.as_bytes()).unwrap();
+        assert_eq!(value.content().as_f64(), Some(expected), "{}", input);
+    }
+    let mut r = CargoStrReader::new("+1");
+    assert!(matches!(
+        read_cargo_number(&mut r),
+        Err(CargoError::InvalidNumber { at: (1, 1) })
+    ));
+}
+
+#[test]
+fn number_must_be_followed_by_a_delimiter() {
+    for (input, found, at) in [
+        ("[1 2]", '2', (1, 4)),
+        ("1.2.3", '.', (1, 4)),
+        ("12x", 'x', (1, 3)),
+        ("[12x]", 'x', (1, 4)),
+        ("[0 1]", '1', (1, 4)),
+        ("01", '1', (1, 2)),
+        ("[1true]", 't', (1, 3)),
+        ("{\"a\":1\"b\":2}", '"', (1, 7)),
+    ] {
+        for err in [
+            parse(input.as_bytes()).unwrap_err(),
+            validate_only(input.as_bytes()).unwrap_err(),
+        ] {
+            assert!(
+                matches!(err, CargoError::UnexpectedChar { found: c, at: pos } if c == found && pos == at),
+                "{}: {}",
+                input,
+                err
+            );
+        }
+    }
+    for input in ["12e", "12e+", "1.", "-", "[1.e5]"] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::InvalidNumber { .. }),
+            "{}: {}",
+            input,
+            err
+        );
+    }
+    assert_eq!(parse(&b"[1,2]"[..]).unwrap().to_string(), "[1,2]");
+    assert_eq!(parse(&b" [ 1 ,\n2 ] "[..]).unwrap().to_string(), "[1,2]");
+}
+
+#[test]
+fn iterative_parser_agrees_with_recursive() {
+    let iterative = ParseConfig {
+        iterative: true,
+        ..ParseConfig::default()
+    };
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        let expected = parse(File::open(&path).unwrap()).unwrap();
+        let value = from_reader_with_config(File::open(&path).unwrap(), &iterative).unwrap();
+        assert_eq!(value, expected, "{}", path);
+        assert_eq!(value.to_string(), expected.to_string(), "{}", path);
+    }
+    let value =
+        from_reader_with_config(&b"{\"a\":{\"b\":[1,{\"c\":null}]}}"[..], &iterative).unwrap();
+    let inner = value.content().get("a").unwrap();
+    assert_eq!(inner.name(), Some("a"));
+    assert_eq!(inner.content().get("b").unwrap().name(), Some("b"));
+    for input in ["[1, 2", "{\"a\" 1}", "[]]", "\"\\x\"", "01", "[1,]", ""] {
+        let expected = parse(input.as_bytes()).unwrap_err().to_string();
+        let err = from_reader_with_config(input.as_bytes(), &iterative).unwrap_err();
+        assert_eq!(err.to_string(), expected, "{}", input);
+    }
+    let config = ParseConfig {
+        reject_duplicate_keys: true,
+        ..iterative
+    };
+    let err =
+        from_reader_with_config(&b"{\"a\":1,\"b\":{\"a\":2},\"a\":3}"[..], &config).unwrap_err();
+    assert!(matches!(err, CargoError::DuplicateKey(ref name) if name == "a"));
+    assert!(from_reader_with_config(&b"{\"a\":1,\"b\":{\"a\":2}}"[..], &config).is_ok());
+}
+
+#[test]
+fn iterative_parser_handles_deep_nesting() {
+    let config = ParseConfig {
+        iterative: true,
+        max_depth: None,
+        ..ParseConfig::default()
+    };
+    let depth = 50000;
+    let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    let value = from_reader_with_config(input.as_bytes(), &config).unwrap();
+    let mut current = &value;
+    let mut levels = 1;
+    while let Some(elements) = current.content().as_array().filter(|e| !e.is_empty()) {
+        current = &elements[0];
+        levels += 1;
+    }
+    assert_eq!(levels, depth);
+    let input = format!("{}1{}", "{\"a\":".repeat(depth), "}".repeat(depth));
+    assert!(from_reader_with_config(input.as_bytes(), &config).is_ok());
+}
+
+#[test]
+fn parse_many_concatenated_values() {
+    let input = "{\"a\":1}\n{\"b\":[true]}\n  {\"c\":null}\n\n";
+    let values: Vec<String> = parse_many(input.as_bytes())
+        .map(|value| value.unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["{\"a\":1}", "{\"b\":[true]}", "{\"c\":null}"]);
+    let values: Vec<String> = parse_many(&b"{}[]\"s\" 1 2"[..])
+        .map(|value| value.unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["{}", "[]", "\"s\"", "1", "2"]);
+    assert_eq!(parse_many(&b" \n "[..]).count(), 0);
+    let results: Vec<_> = parse_many(&b"[1] [2,] [3]"[..]).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(CargoError::UnexpectedChar {
+            found: ']',
+            at: (1, 8)
+        })
+    ));
+}
+
+#[test]
+fn string_and_number_length_limits() {
+    let config = ParseConfig {
+        max_string_len: 8,
+        max_number_len: 6,
+        ..ParseConfig::default()
+    };
+    let parse_limited = |input: &str| from_reader_with_config(input.as_bytes(), &config);
+    for input in [
+        "[\"12345678\"]",
+        "{\"12345678\":1}",
+        "\"\\u00e9\\u00e9\\u00e9\\u00e9\"",
+        "[123456]",
+        "-12345",
+        "1.2e+3",
+    ] {
+        assert!(parse_limited(input).is_ok(), "{}", input);
+    }
+    for (input, at) in [
+        ("[\"123456789\"]", (1, 2)),
+        ("{\"123456789\":1}", (1, 2)),
+        ("\"\\u00e9\\u00e9\\u00e9\\u00e9a\"", (1, 1)),
+        ("[\"日本語\"]", (1, 2)),
+        ("[1234567]", (1, 2)),
+        ("-123456", (1, 1)),
+        ("1.2e+34", (1, 1)),
+    ] {
+        let err = parse_limited(input).unwrap_err();
+        assert!(
+            matches!(err, CargoError::LimitExceeded { at: pos } if pos == at),
+            "{}: {}",
+            input,
+            err
+        );
+    }
+    // The limit applies as the input is read, so reading stops there.
+    let input = format!("[\"{}", "x".repeat(100));
+    assert!(matches!(
+        parse_limited(&input).unwrap_err(),
+        CargoError::LimitExceeded { .. }
+    ));
+    assert!(parse(format!("\"{}\"", "x".repeat(100_000)).as_bytes()).is_ok());
+}
+
+#[test]
+fn custom_config_end_to_end() {
+    let config = ParseConfig::new()
+        .relaxed(true)
+        .reject_duplicate_keys(true)
+        .max_string_len(5)
+        .max_number_len(4);
+    assert!(config.relaxed && config.reject_duplicate_keys && !config.iterative);
+    let input = "// config\n{\"name\": \"cargo\", \"n\": [1, 2.5,], /* x */ \"m\": {},}";
+    let value = from_reader_with_config(input.as_bytes(), &config).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "{\"name\":\"cargo\",\"n\":[1,0.25e1],\"m\":{}}"
+    );
+    assert!(parse(input.as_bytes()).is_err());
+    let iterative = config.clone().iterative(true);
+    assert_eq!(
+        from_reader_with_config(input.as_bytes(), &iterative).unwrap(),
+        value
+    );
+    for config in [&config, &iterative] {
+        for (input, expected) in [
+            ("{\"a\":1,\"a\":2,}", "duplicate member name \"a\""),
+            ("[\"cargos\"]", "string or number too long"),
+            ("[12345, ]", "string or number too long"),
+        ] {
+            let err = from_reader_with_config(input.as_bytes(), config).unwrap_err();
+            assert!(err.to_string().ends_with(expected), "{}: {}", input, err);
+        }
+    }
+    let default = ParseConfig::new();
+    assert!(!default.relaxed && !default.reject_duplicate_keys);
+    assert_eq!(
+        default.max_string_len,
+        ParseConfig::default().max_string_len
+    );
+}
+
+#[test]
+fn is_valid_predicate() {
+    for input in [
+        "0",
+        " {\"a\": [1, 2.5e3, null]} ",
+        "\"\\u00e9\"",
+        "[]",
+        "true\n",
+    ] {
+        assert!(is_valid(input), "{:?}", input);
+    }
+    for input in ["", " ", "[1,]", "{\"a\"}", "01", "tru", "1 2", "\"\\x\""] {
+        assert!(!is_valid(input), "{:?}", input);
+    }
+}
+
+#[test]
+fn integers_beyond_i64_fall_back_to_float() {
+    let number = |s: &str| match parse(s.as_bytes()).unwrap().into_content() {
+        CargoContent::Number(n) => n,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(number("9223372036854775807").as_i64(), Some(i64::MAX));
+    assert_eq!(number("-9223372036854775808").as_i64(), Some(i64::MIN));
+    for text in [
+        "9223372036854775808",
+        "-9223372036854775809",
+        "18446744073709551616",
+        "99999999999999999999",
+    ] {
+        let n = number(text);
+        assert_eq!(n.as_i64(), None, "{}", text);
+        assert_eq!(n.as_f64(), Some(text.parse::<f64>().unwrap()));
+    }
+    // With the "bigdecimal" feature, such a number is written exactly.
+    let expected = if cfg!(feature = "bigdecimal") {
+        "0.99999999999999999999e20"
+    } else {
+        "0.1e21"
+    };
+    assert_eq!(canonicalize_str("99999999999999999999").unwrap(), expected);
+}
+
+#[test]
+fn preserve_order_option() {
+    let canonical = |input: &str, config: ParseConfig| {
+        from_reader_with_config(input.as_bytes(), &config)
+            .unwrap()
+            .to_string()
+    };
+    let input = "{\"z\":1,\"a\":2,\"m\":{\"y\":[{\"c\":3,\"b\":4}],\"x\":5},\"a\":6}";
+    for iterative in [false, true] {
+        let config = ParseConfig::new().iterative(iterative);
+        assert_eq!(canonical(input, config.clone()), input);
+        assert_eq!(canonical(input, config.clone().preserve_order(true)), input);
+        assert_eq!(
+            canonical(input, config.preserve_order(false)),
+            "{\"a\":2,\"a\":6,\"m\":{\"x\":5,\"y\":[{\"b\":4,\"c\":3}]},\"z\":1}"
+        );
+    }
+}
+
+#[test]
+fn tokenize_yields_tokens_with_spans() {
+    let tokens: Vec<(CargoToken, CargoSpan)> = tokenize("{\"a\":[1,true]}".as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let kinds: Vec<&CargoToken> = tokens.iter().map(|(token, _)| token).collect();
+    assert_eq!(
+        kinds,
+        [
+            &CargoToken::LBrace,
+            &CargoToken::StringLit("a".into()),
+            &CargoToken::Colon,
+            &CargoToken::LBracket,
+            &CargoToken::NumberLit("1".into()),
+            &CargoToken::Comma,
+            &CargoToken::True,
+            &CargoToken::RBracket,
+            &CargoToken::RBrace,
+        ]
+    );
+    assert_eq!(
+        tokens[1].1,
+        CargoSpan {
+            start: (1, 2),
+            end: (1, 5)
+        }
+    );
+    assert_eq!(
+        tokens[6].1,
+        CargoSpan {
+            start: (1, 9),
+            end: (1, 13)
+        }
+    );
+}
+
+#[test]
+fn tokenize_ignores_structure_and_stops_at_errors() {
+    let tokens: Vec<_> = tokenize(" ]\n-1.5e3 \"\\u00e9\" null false, [tru".as_bytes()).collect();
+    assert_eq!(tokens.len(), 8);
+    assert_eq!(
+        tokens[1].as_ref().unwrap().0,
+        CargoToken::NumberLit("-1.5e3".into())
+    );
+    assert_eq!(tokens[1].as_ref().unwrap().1.start, (2, 1));
+    assert_eq!(
+        tokens[2].as_ref().unwrap().0,
+        CargoToken::StringLit("\u{e9}".into())
+    );
+    assert_eq!(tokens[4].as_ref().unwrap().0, CargoToken::False);
+    assert_eq!(tokens[6].as_ref().unwrap().0, CargoToken::LBracket);
+    assert!(tokens[7].is_err());
+    assert_eq!(tokenize("".as_bytes()).count(), 0);
+}
+
+/*
+ * A reader that hands out its input a byte at a time, failing with the given
+ * error before each byte for which it is Some.
+ */
+struct FlakyReader {
+    steps: Vec<Result<u8, io::ErrorKind>>,
+}
+
+impl io::Read for FlakyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.steps.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        match self.steps.remove(0) {
+            Ok(b) => {
+                buf[0] = b;
+                Ok(1)
+            }
+            Err(kind) => Err(io::Error::from(kind)),
+        }
+    }
+}
+
+fn flaky(input: &str, fail_before: &[(usize, io::ErrorKind)]) -> FlakyReader {
+    let mut steps = Vec::new();
+    for (i, b) in input.bytes().enumerate() {
+        for &(at, kind) in fail_before {
+            if at == i {
+                steps.push(Err(kind));
+            }
+        }
+        steps.push(Ok(b));
+    }
+    FlakyReader { steps }
+}
+
+#[test]
+fn interrupted_and_short_reads_are_retried() {
+    let input = "{\"caf\u{e9}\": [1.5, true]}";
+    let expected = parse(input.as_bytes()).unwrap();
+    let interrupted = [(0, io::ErrorKind::Interrupted)];
+    assert_eq!(parse(flaky(input, &interrupted)).unwrap(), expected);
+    let everywhere: Vec<_> = (0..input.len())
+        .map(|i| (i, io::ErrorKind::Interrupted))
+        .collect();
+    assert_eq!(parse(flaky(input, &everywhere)).unwrap(), expected);
+    assert!(validate_only(flaky(input, &everywhere)).is_ok());
+}
+
+#[test]
+fn unterminated_containers_report_their_opener() {
+    let input = "{\"a\": [1, 2],\n \"b\": {\"c\": true";
+    for config in [ParseConfig::default(), ParseConfig::new().iterative(true)] {
+        let err = from_reader_with_config(input.as_bytes(), &config).unwrap_err();
+        assert!(
+            matches!(err, CargoError::UnterminatedContainer { opened_at: (2, 7) }),
+            "{:?}",
+            err
+        );
+    }
+    assert!(matches!(
+        validate_only(input.as_bytes()).unwrap_err(),
+        CargoError::UnterminatedContainer { opened_at: (2, 7) }
+    ));
+    for input in [
+        "{",
+        "{\"a\"",
+        "{\"a\":",
+        "{\"a\": 1",
+        "{\"a\": 1,",
+        "[",
+        "[1",
+        "[1,",
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::UnterminatedContainer { opened_at: (1, 1) }),
+            "{:?}: {:?}",
+            input,
+            err
+        );
+    }
+    assert_eq!(
+        parse(&b"  [[1]"[..]).unwrap_err().to_string(),
+        "error at line 1, column 3: object or array opened here is never closed"
+    );
+}
+
+#[test]
+fn end_of_input_mid_token_is_unexpected_eof() {
+    for input in ["\"abc", "[1, tr", "{\"a\": nu"] {
+        let err = parse(flaky(input, &[(1, io::ErrorKind::Interrupted)])).unwrap_err();
+        assert!(matches!(err, CargoError::UnexpectedEof { .. }), "{:?}", err);
+    }
+    let err = parse(flaky("[1, 2]", &[(3, io::ErrorKind::BrokenPipe)])).unwrap_err();
+    assert!(matches!(err, CargoError::Io(_)), "{:?}", err);
+}
+
+#[test]
+fn max_depth_limits_nesting() {
+    for iterative in [false, true] {
+        let config = ParseConfig::new().iterative(iterative).max_depth(Some(2));
+        let parse_with = |input: &str| from_reader_with_config(input.as_bytes(), &config);
+        assert!(parse_with("[[1], {\"a\": 2}]").is_ok());
+        assert!(parse_with("1").is_ok());
+        let err = parse_with("[1, {\"a\": [\n {}]}]").unwrap_err();
+        assert!(
+            matches!(err, CargoError::DepthExceeded { at: (1, 11) }),
+            "{:?}",
+            err
+        );
+        let scalar_only = ParseConfig::new().iterative(iterative).max_depth(Some(0));
+        assert!(from_reader_with_config("true".as_bytes(), &scalar_only).is_ok());
+        assert!(from_reader_with_config("[]".as_bytes(), &scalar_only).is_err());
+    }
+}
+
+#[test]
+fn read_cargo_number_from_str_reader() {
+    let mut r = CargoStrReader::new("-12.5e3, 7");
+    let number = read_cargo_number(&mut r).unwrap();
+    assert_eq!(number.as_f64(), Some(-12500.0));
+    assert_eq!(number.as_i64(), None);
+    assert_eq!(r.pos(), (1, 8));
+    assert_eq!(r.next().unwrap(), Some(','));
+    assert_eq!(r.next().unwrap(), Some(' '));
+    assert_eq!(read_cargo_number(&mut r).unwrap().as_i64(), Some(7));
+    assert_eq!(r.peek().unwrap(), None);
+
+    let mut r = CargoStrReader::new("0123");
+    assert_eq!(read_cargo_number(&mut r).unwrap().as_i64(), Some(0));
+    assert_eq!(r.peek().unwrap(), Some('1'));
+    for (input, at) in [
+        ("-", (1, 1)),
+        ("1.", (1, 1)),
+        ("\n 1e+", (2, 2)),
+        ("x", (1, 1)),
+    ] {
+        let mut r = CargoStrReader::new(input);
+        while r.peek().unwrap().is_some_and(char::is_whitespace) {
+            r.next().unwrap();
+        }
+        assert!(
+            matches!(read_cargo_number(&mut r), Err(CargoError::InvalidNumber { at: pos }) if pos == at),
+            "{:?}",
+            input
+        );
+    }
+    let config = ParseConfig::new().max_number_len(3);
+    let mut r = CargoStrReader::with_config("1234", config);
+    assert!(matches!(
+        read_cargo_number(&mut r),
+        Err(CargoError::LimitExceeded { .. })
+    ));
+}
+
+#[test]
+fn parse_str_agrees_with_parse() {
+    for input in [
+        "{\"caf\u{e9}\": [1, 2.5, \"\\ud83d\\ude00\"]}\n",
+        "[true,\r\n null]",
+        "\"\u{feff}\"",
+        "[1,]",
+        "\"unterminated",
+        "{\"a\":\n 1\n \"b\": 2}",
+    ] {
+        match (parse_str(input), parse(input.as_bytes())) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+            other => panic!("{:?}: {:?}", input, other),
+        }
+    }
+    assert!(is_valid("[1]") && !is_valid("[1"));
+}
+
+#[test]
+fn validate_all_reports_every_error() {
+    let input = "{\n  \"a\": [1 2],\n  \"b\": tru,\n  \"c\" 3,\n  \"d\": {\"e\": 1,}\n}";
+    let errors = validate_all(input.as_bytes());
+    let found: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(
+        found,
+        [
+            "error at line 2, column 11: unexpected character '2'",
+            "error at line 3, column 11: unexpected character ','",
+            "error at line 4, column 7: unexpected character '3'",
+            "error at line 5, column 16: unexpected character '}'",
+        ]
+    );
+    for name in ["simple", "average", "diff_space", "long", "non_ascii"] {
+        let path = format!("tests/test_inputs/{}_inp.json", name);
+        assert!(
+            validate_all(File::open(&path).unwrap()).is_empty(),
+            "{}",
+            path
+        );
+    }
+    // The first error is the one that validate_only reports.
+    for input in [
+        "",
+        "[1, 2",
+        "{\"a\" 1}",
+        "[1,]",
+        "01",
+        "[1] 2",
+        "{\"a\": [}",
+        "[\"\\x\", 1 1]",
+        "]",
+        "{\"a\":1 \"b\":2}",
+        "[{]",
+        "-",
+        "[1, {\"a\": nul}]",
+        "\"abc",
+    ] {
+        let errors = validate_all(input.as_bytes());
+        let expected = validate_only(input.as_bytes()).unwrap_err();
+        assert!(!errors.is_empty(), "{:?}", input);
+        assert_eq!(errors[0].to_string(), expected.to_string(), "{:?}", input);
+    }
+    let errors = validate_all(&b"[1 2, {\"a\" 3}, [4 5"[..]);
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(matches!(
+        errors[3],
+        CargoError::UnterminatedContainer { opened_at: (1, 16) }
+    ));
+}
+
+/*
+ * Reads the rest of the input, checking that each character is at the position
+ * that the reader reports for it.
+ */
+fn read_rest_with_positions<R: CargoRead>(r: &mut R) -> Vec<(char, (usize, usize))> {
+    let mut read = Vec::new();
+    loop {
+        let at = r.pos();
+        match r.next().unwrap() {
+            Some(c) => read.push((c, at)),
+            None => return read,
+        }
+    }
+}
+
+fn reset_rereads_after_speculative_parse<R: CargoRead>(mut r: R, input: &str) {
+    assert!(matches!(
+        r.reset(),
+        Err(CargoError::MarkLost { at: (1, 1) })
+    ));
+    r.next().unwrap();
+    r.mark();
+    assert!(matches!(
+        read_cargo_number(&mut r),
+        Err(CargoError::InvalidNumber { at: (1, 2) })
+    ));
+    r.reset().unwrap();
+    assert_eq!(r.pos(), (1, 2));
+    let first = read_rest_with_positions(&mut r);
+    assert_eq!(
+        first.iter().map(|&(c, _)| c).collect::<String>(),
+        &input[1..]
+    );
+    // The mark is kept, so the same characters can be read yet again.
+    r.reset().unwrap();
+    assert_eq!(read_rest_with_positions(&mut r), first);
+}
+
+#[test]
+fn reset_returns_to_the_mark() {
+    let input = "[12.5e\n\u{e9}]";
+    reset_rereads_after_speculative_parse(CargoStrReader::new(input), input);
+    reset_rereads_after_speculative_parse(CargoReader::new(input.as_bytes()), input);
+
+    // A CargoReader buffers only so much lookahead before letting the mark go.
+    let input = " ".repeat(CARGO_MAX_LOOKAHEAD + 1);
+    let mut r = CargoReader::new(input.as_bytes());
+    r.mark();
+    for _ in 0..CARGO_MAX_LOOKAHEAD {
+        r.next().unwrap();
+    }
+    r.reset().unwrap();
+    assert_eq!(r.pos(), (1, 1));
+    for _ in 0..=CARGO_MAX_LOOKAHEAD {
+        r.next().unwrap();
+    }
+    assert!(matches!(
+        r.reset(),
+        Err(CargoError::MarkLost { at: (1, 4098) })
+    ));
+    assert_eq!(r.next().unwrap(), None);
+}
+
+#[test]
+fn default_config_limits_depth() {
+    let limit = CARGO_MAX_DEPTH;
+    let nested = format!("{}{}", "[".repeat(limit), "]".repeat(limit));
+    assert_eq!(parse_str(&nested).unwrap().to_string(), nested);
+    let too_deep = format!("[{}]", nested);
+    let at = (1, limit + 1);
+    for input in [
+        "[".repeat(200_000),
+        "[".repeat(100_000) + &"]".repeat(100_000),
+        too_deep,
+    ] {
+        let err = parse(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::DepthExceeded { at: a } if a == at),
+            "{:?}",
+            err
+        );
+        let err = parse_str(&input).unwrap_err();
+        assert!(
+            matches!(err, CargoError::DepthExceeded { at: a } if a == at),
+            "{:?}",
+            err
+        );
+        let err = validate_only(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, CargoError::DepthExceeded { at: a } if a == at),
+            "{:?}",
+            err
+        );
+    }
+}
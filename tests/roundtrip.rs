@@ -0,0 +1,47 @@
+/*
+ * The invariant checked here is that canonical form is a fixed point: writing
+ * any value in canonical form, reading that text back in, and writing the result
+ * again must give exactly the same text. A writer that drops or mangles anything
+ * that the parser then reads differently (or fails to read at all) breaks this.
+ */
+use proptest::prelude::*;
+use rs_cargo::{
+    canonicalize_str, CargoArray, CargoBasic, CargoContent, CargoNumber, CargoObject, CargoString,
+    CargoValue,
+};
+
+fn arb_value() -> impl Strategy<Value = CargoValue> {
+    let leaf = prop_oneof![
+        any::<()>().prop_map(|_| CargoContent::Basic(CargoBasic::CargoNull)),
+        any::<bool>().prop_map(|b| CargoContent::Basic(if b {
+            CargoBasic::CargoTrue(true)
+        } else {
+            CargoBasic::CargoFalse(false)
+        })),
+        any::<i64>().prop_map(|i| CargoContent::Number(CargoNumber::from(i))),
+        any::<f64>()
+            .prop_filter("numbers must be finite", |f| f.is_finite())
+            .prop_map(|f| CargoContent::Number(CargoNumber::try_from(f).unwrap())),
+        any::<String>().prop_map(|s| CargoContent::String(CargoString::from(s))),
+    ]
+    .prop_map(CargoValue::from);
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(|elements| {
+                CargoValue::from(CargoContent::Array(Box::new(CargoArray::from(elements))))
+            }),
+            prop::collection::vec((any::<String>(), inner), 0..8).prop_map(|members| {
+                CargoValue::from(CargoContent::Object(Box::new(CargoObject::from(members))))
+            }),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn canonical_form_is_idempotent(value in arb_value()) {
+        let canonical = value.to_string();
+        let again = canonicalize_str(&canonical).unwrap();
+        prop_assert_eq!(again, canonical);
+    }
+}
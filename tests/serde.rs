@@ -0,0 +1,23 @@
+#![cfg(feature = "serde")]
+
+use rs_cargo::CargoValue;
+
+const DOCUMENT: &str = "{\"a\":[1,-2,0.25e1,\"x\\u0101\"],\"b\":{\"c\":true,\"d\":null}}";
+
+#[test]
+fn round_trip_through_serde_json() {
+    let value: CargoValue = DOCUMENT.parse().unwrap();
+    let json = serde_json::to_string(&value).unwrap();
+    let back: CargoValue = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.to_string(), DOCUMENT);
+}
+
+#[test]
+fn convert_to_and_from_serde_json_value() {
+    let value: CargoValue = DOCUMENT.parse().unwrap();
+    let json = serde_json::to_value(&value).unwrap();
+    assert_eq!(json["a"][0], serde_json::json!(1));
+    assert_eq!(json["b"]["c"], serde_json::json!(true));
+    let back: CargoValue = serde_json::from_value(json).unwrap();
+    assert_eq!(back.to_string(), DOCUMENT);
+}
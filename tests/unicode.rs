@@ -0,0 +1,27 @@
+#![cfg(feature = "unicode-normalization")]
+
+use rs_cargo::{parse_str, write_cargo_value, WriteOpts};
+
+fn canonical(input: &str, normalize_unicode: bool) -> String {
+    let opts = WriteOpts {
+        normalize_unicode,
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &parse_str(input).unwrap(), &opts).unwrap();
+    out
+}
+
+#[test]
+fn composed_and_decomposed_text_canonicalize_alike_when_normalized() {
+    let composed = "{\"caf\\u00e9\": \"r\\u00e9sum\\u00e9\"}";
+    let decomposed = "{\"cafe\\u0301\": \"re\\u0301sume\\u0301\"}";
+    let expected = "{\"café\":\"résumé\"}";
+    assert_eq!(canonical(composed, true), expected);
+    assert_eq!(canonical(decomposed, true), expected);
+    assert_eq!(canonical(composed, false), expected);
+    assert_eq!(
+        canonical(decomposed, false),
+        "{\"cafe\\u0301\":\"re\\u0301sume\\u0301\"}"
+    );
+}
@@ -0,0 +1,764 @@
+#![cfg(feature = "std")]
+
+use rs_cargo::{
+    canonicalize_str, canonicalize_stream, from_reader_with_config, parse, pretty_str,
+    write_cargo_value, CargoContent, CargoError, CargoLayout, CargoNumber, CargoString, CargoValue,
+    IndentStyle, LineEnding, ParseConfig, WriteOpts,
+};
+use std::{
+    fs::{self, File},
+    io,
+};
+
+fn pretty(value: &CargoValue, indent: u32) -> String {
+    let mut out = String::new();
+    let opts = WriteOpts {
+        indent: Some(IndentStyle::Spaces(indent)),
+        ..WriteOpts::default()
+    };
+    write_cargo_value(&mut out, value, &opts).unwrap();
+    out
+}
+
+#[test]
+fn display_is_compact_canonical_form() {
+    let value =
+        parse(&b"{ \"a\" : [ 1, -20, 1.5, 1e3, 0.0 ],\n \"b\": [true, false, null] }"[..]).unwrap();
+    assert_eq!(
+        format!("{}", value),
+        "{\"a\":[1,-20,0.15e1,0.1e4,0.0],\"b\":[true,false,null]}"
+    );
+    assert_eq!(format!("{}", value.content()), value.to_string());
+}
+
+#[test]
+fn display_matches_canonical_strings() {
+    let value = parse(File::open("rsrc/strings.json").unwrap()).unwrap();
+    let expected = fs::read_to_string("tests/rsrc/strings_-c.json").unwrap();
+    assert_eq!(format!("{}", value), expected);
+}
+
+#[test]
+fn pretty_print_matches_expected_outputs() {
+    for (name, indent) in [
+        ("simple", 8),
+        ("average", 4),
+        ("diff_space", 5),
+        ("long", 10),
+        ("non_ascii", 8),
+    ] {
+        let input = File::open(format!("tests/test_inputs/{}_inp.json", name)).unwrap();
+        let expected =
+            fs::read_to_string(format!("tests/test_outputs/exp_{}_out.json", name)).unwrap();
+        assert_eq!(pretty(&parse(input).unwrap(), indent), expected, "{}", name);
+    }
+}
+
+#[test]
+fn pretty_print_is_idempotent() {
+    let expected = fs::read_to_string("rsrc/package-lock.json").unwrap();
+    let value = parse(expected.as_bytes()).unwrap();
+    assert_eq!(pretty(&value, 2), expected);
+}
+
+#[test]
+fn verbatim_numbers_are_written_unchanged() {
+    let input = "[123456789012345678901234567890,-0.10E+02,3]";
+    let value = parse(input.as_bytes()).unwrap();
+    let mut out = String::new();
+    let opts = WriteOpts {
+        verbatim_numbers: true,
+        ..WriteOpts::default()
+    };
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, input);
+    // With the "bigdecimal" feature, the long number keeps all of its digits.
+    let expected = if cfg!(feature = "bigdecimal") {
+        "[0.12345678901234567890123456789e30,-0.1e2,3]"
+    } else {
+        "[0.123456789012346e30,-0.1e2,3]"
+    };
+    assert_eq!(value.to_string(), expected);
+}
+
+#[test]
+fn write_multibyte_strings() {
+    let value = parse("[\"café\",\"日本語\"]".as_bytes()).unwrap();
+    assert_eq!(value.to_string(), "[\"café\",\"\\u65e5\\u672c\\u8a9e\"]");
+}
+
+#[test]
+fn canonicalize_str_removes_whitespace() {
+    let input = " {\n\t\"b\" : [ 1 , 2.50 , 1E3 ] ,\r\n \"a\" : \"x y\" , \"c\":{ } }\n";
+    let canonical = canonicalize_str(input).unwrap();
+    assert_eq!(canonical, "{\"b\":[1,0.25e1,0.1e4],\"a\":\"x y\",\"c\":{}}");
+    assert_eq!(canonicalize_str(input).unwrap(), canonical);
+    assert_eq!(canonicalize_str(&canonical).unwrap(), canonical);
+    assert!(canonicalize_str("[1,").is_err());
+}
+
+#[test]
+fn pretty_str_indents_output() {
+    assert_eq!(
+        pretty_str("{\"a\":[1,true]}", 2).unwrap(),
+        "{\n  \"a\": [\n    1,\n    true\n  ]\n}\n"
+    );
+    let input = fs::read_to_string("tests/test_inputs/average_inp.json").unwrap();
+    let expected = fs::read_to_string("tests/test_outputs/exp_average_out.json").unwrap();
+    assert_eq!(pretty_str(&input, 4).unwrap(), expected);
+}
+
+#[test]
+fn streaming_canonicalizer_matches_tree() {
+    let mut input = String::from("[");
+    for i in 0..5000 {
+        input.push_str(&format!(
+            "{} {{\"id\": {}, \"x\": -{}.25e-3, \"tags\": [\"t{}\", true, null, []], \"e\": {{}}}}\n",
+            if i > 0 { "," } else { "" },
+            i,
+            i,
+            i
+        ));
+    }
+    input.push(']');
+    let value = parse(input.as_bytes()).unwrap();
+    for indent in [
+        None,
+        Some(IndentStyle::Spaces(0)),
+        Some(IndentStyle::Spaces(3)),
+        Some(IndentStyle::Tabs),
+    ] {
+        let opts = WriteOpts {
+            indent,
+            ..WriteOpts::default()
+        };
+        let mut expected = String::new();
+        write_cargo_value(&mut expected, &value, &opts).unwrap();
+        let mut out = Vec::new();
+        canonicalize_stream(input.as_bytes(), &mut out, &opts).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected, "{:?}", indent);
+    }
+    let mut out = Vec::new();
+    assert!(canonicalize_stream(&b"[1, 2,]"[..], &mut out, &WriteOpts::default()).is_err());
+    assert!(canonicalize_stream(&b"[1] 2"[..], &mut out, &WriteOpts::default()).is_err());
+}
+
+#[test]
+fn streaming_canonicalizer_matches_expected_outputs() {
+    for (name, indent) in [
+        ("simple", 8),
+        ("average", 4),
+        ("diff_space", 5),
+        ("long", 10),
+        ("non_ascii", 8),
+    ] {
+        let input = File::open(format!("tests/test_inputs/{}_inp.json", name)).unwrap();
+        let expected =
+            fs::read_to_string(format!("tests/test_outputs/exp_{}_out.json", name)).unwrap();
+        let opts = WriteOpts {
+            indent: Some(IndentStyle::Spaces(indent)),
+            ..WriteOpts::default()
+        };
+        let mut out = Vec::new();
+        canonicalize_stream(input, &mut out, &opts).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected, "{}", name);
+    }
+}
+
+#[test]
+fn non_finite_floats_are_refused() {
+    for f in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(CargoValue::number_f64(f), None, "{}", f);
+        let err = CargoNumber::try_from(f).unwrap_err();
+        assert!(matches!(err, CargoError::NotFinite), "{}", f);
+    }
+    let value = CargoValue::number_f64(-1.5e-300).unwrap();
+    assert_eq!(value.to_string(), "-0.15e-299");
+    assert_eq!(value.to_string_pretty(2), "-0.15e-299\n");
+}
+
+#[test]
+fn constructed_document_canonicalizes() {
+    let document = CargoValue::object(vec![
+        (String::from("name"), CargoValue::string("rs-cargo")),
+        (String::from("version"), CargoValue::number_i64(3)),
+        (String::from("ratio"), CargoValue::number_f64(0.5).unwrap()),
+        (
+            String::from("flags"),
+            CargoValue::array(vec![
+                CargoValue::bool(true),
+                CargoValue::bool(false),
+                CargoValue::null(),
+            ]),
+        ),
+        (String::from("empty"), CargoValue::object(Vec::new())),
+    ]);
+    assert_eq!(
+        document.to_string(),
+        "{\"name\":\"rs-cargo\",\"version\":3,\"ratio\":0.5,\"flags\":[true,false,null],\"empty\":{}}"
+    );
+    assert_eq!(
+        document.content().get("version").unwrap().name(),
+        Some("version")
+    );
+    assert_eq!(
+        canonicalize_str(&document.to_string()).unwrap(),
+        document.to_string()
+    );
+}
+
+#[test]
+fn pretty_print_with_zero_indent() {
+    let value = parse(&b"{\"a\": {\"b\": [1, {}]}}"[..]).unwrap();
+    assert_eq!(
+        pretty(&value, 0),
+        "{\n\"a\": {\n\"b\": [\n1,\n{\n}\n]\n}\n}\n"
+    );
+}
+
+#[test]
+fn pretty_print_with_tabs() {
+    let value = parse(&b"{\"a\": {\"b\": [1, {}]}}"[..]).unwrap();
+    let opts = WriteOpts {
+        indent: Some(IndentStyle::Tabs),
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(
+        out,
+        "{\n\t\"a\": {\n\t\t\"b\": [\n\t\t\t1,\n\t\t\t{\n\t\t\t}\n\t\t]\n\t}\n}\n"
+    );
+}
+
+#[test]
+fn write_named_member() {
+    let value = parse(&b"{\"name\": [1, {\"x\": null}], \"n\": 2}"[..]).unwrap();
+    let member = value.content().get("name").unwrap();
+    let mut out = String::new();
+    member.write(&mut out, &WriteOpts::default()).unwrap();
+    assert_eq!(out, "\"name\":[1,{\"x\":null}]");
+    let opts = WriteOpts {
+        indent: Some(IndentStyle::Spaces(2)),
+        ..WriteOpts::default()
+    };
+    out.clear();
+    member.write(&mut out, &opts).unwrap();
+    assert_eq!(out, "\"name\": [\n  1,\n  {\n    \"x\": null\n  }\n]");
+    out.clear();
+    value.write(&mut out, &WriteOpts::default()).unwrap();
+    assert_eq!(out, value.to_string());
+}
+
+#[test]
+fn solidus_escape_is_accepted_but_not_written() {
+    let value = parse(&b"\"http:\\/\\/x/y\""[..]).unwrap();
+    assert_eq!(value.content().as_str(), Some("http://x/y"));
+    assert_eq!(value.to_string(), "\"http://x/y\"");
+    assert_eq!(
+        canonicalize_str("[\"\\/\", \"/\"]").unwrap(),
+        "[\"/\",\"/\"]"
+    );
+}
+
+#[test]
+fn collapse_small_simple_containers() {
+    let value = parse(
+        &b"{\"point\": [1, 2, 3], \"big\": [1, 2, 3, 4], \"nested\": [[1], {}], \"o\": {\"a\": \"x\", \"b\": null}, \"e\": []}"[..],
+    )
+    .unwrap();
+    let mut opts = WriteOpts {
+        indent: Some(IndentStyle::Spaces(2)),
+        collapse_threshold: Some(4),
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(
+        out,
+        "{\n  \"point\": [1, 2, 3],\n  \"big\": [\n    1,\n    2,\n    3,\n    4\n  ],\n  \"nested\": [\n    [1],\n    {}\n  ],\n  \"o\": {\"a\": \"x\", \"b\": null},\n  \"e\": []\n}\n"
+    );
+    opts.collapse_threshold = None;
+    out.clear();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, pretty(&value, 2));
+    assert!(out.contains("\"point\": [\n    1,"));
+    // Without pretty-printing, the threshold makes no difference.
+    opts.indent = None;
+    opts.collapse_threshold = Some(4);
+    out.clear();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, value.to_string());
+}
+
+#[test]
+fn sort_keys_orders_members_by_name() {
+    let sorted = WriteOpts {
+        sort_keys: true,
+        ..WriteOpts::default()
+    };
+    let write = |input: &str, opts: &WriteOpts| {
+        let mut out = String::new();
+        write_cargo_value(&mut out, &parse(input.as_bytes()).unwrap(), opts).unwrap();
+        out
+    };
+    assert_eq!(write("{\"b\":1,\"a\":2}", &sorted), "{\"a\":2,\"b\":1}");
+    assert_eq!(
+        write("{\"b\":1,\"a\":2}", &WriteOpts::default()),
+        "{\"b\":1,\"a\":2}"
+    );
+    assert_eq!(
+        write(
+            "{\"\\u00e9\":1,\"z\":[{\"y\":1,\"x\":2}],\"Z\":3,\"a\":4,\"a\":5,\"\":6}",
+            &sorted
+        ),
+        "{\"\":6,\"Z\":3,\"a\":4,\"a\":5,\"z\":[{\"x\":2,\"y\":1}],\"é\":1}"
+    );
+}
+
+#[test]
+fn to_writer_and_to_string_methods() {
+    let value = parse(&b"{\"a\": [1, {\"b\": \"x\"}], \"c\": 1.5}"[..]).unwrap();
+    let compact = "{\"a\":[1,{\"b\":\"x\"}],\"c\":0.15e1}";
+    let pretty =
+        "{\n  \"a\": [\n    1,\n    {\n      \"b\": \"x\"\n    }\n  ],\n  \"c\": 0.15e1\n}\n";
+    assert_eq!(value.to_string(), compact);
+    assert_eq!(value.to_string_pretty(2), pretty);
+    let mut out = Vec::new();
+    value.to_writer(&mut out).unwrap();
+    assert_eq!(out, compact.as_bytes());
+    out.clear();
+    value.to_writer_pretty(&mut out, 2).unwrap();
+    assert_eq!(out, pretty.as_bytes());
+}
+
+#[test]
+fn precision_limits_significant_digits() {
+    let value: CargoValue = "[3.14159, 2.5, 1e-7, 100, 0.0, -1234.5678]"
+        .parse()
+        .unwrap();
+    let with_precision = |precision| {
+        let opts = WriteOpts {
+            precision: Some(precision),
+            ..WriteOpts::default()
+        };
+        let mut out = String::new();
+        write_cargo_value(&mut out, &value, &opts).unwrap();
+        out
+    };
+    assert_eq!(
+        with_precision(3),
+        "[0.314e1,0.25e1,0.1e-6,100,0.0,-0.123e4]"
+    );
+    assert_eq!(with_precision(1), "[0.3e1,0.2e1,0.1e-6,100,0.0,-0.1e4]");
+    assert_eq!(with_precision(0), with_precision(1));
+    assert_eq!(
+        with_precision(15),
+        "[0.314159e1,0.25e1,0.1e-6,100,0.0,-0.12345678e4]"
+    );
+    assert_eq!(with_precision(17), with_precision(100));
+    let mut out = String::new();
+    write_cargo_value(
+        &mut out,
+        &"0.1".parse().unwrap(),
+        &WriteOpts {
+            precision: Some(17),
+            ..WriteOpts::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(out, "0.10000000000000001");
+}
+
+#[test]
+fn embedded_nul_round_trips() {
+    let value = parse(&b"[\"a\\u0000b\", \"\\u0000\"]"[..]).unwrap();
+    let elements = value.content().as_array().unwrap();
+    assert_eq!(elements[0].content().as_str(), Some("a\0b"));
+    assert_eq!(elements[0].content().as_str().map(str::len), Some(3));
+    assert_eq!(elements[1].content().as_str(), Some("\0"));
+    let canonical = value.to_string();
+    assert_eq!(canonical, "[\"a\\u0000b\",\"\\u0000\"]");
+    assert!(!canonical.contains('\0'));
+    assert_eq!(canonicalize_str(&canonical).unwrap(), canonical);
+    assert_eq!(CargoValue::string("x\0").to_string(), "\"x\\u0000\"");
+    assert!(matches!(
+        parse(&b"\"a\0b\""[..]).unwrap_err(),
+        rs_cargo::CargoError::UnexpectedChar {
+            found: '\0',
+            at: (1, 3)
+        }
+    ));
+}
+
+#[test]
+fn string_escapes_are_normalized() {
+    for (input, canonical) in [
+        // Printable ASCII is written as itself, however it was escaped.
+        ("\"\\u0041\\u007a\\u0020~\"", "\"Az ~\""),
+        ("\"\\/\"", "\"/\""),
+        // Short escapes are preferred to \u escapes.
+        (
+            "\"\\u000A\\u000d\\u0009\\u0008\\u000C\"",
+            "\"\\n\\r\\t\\b\\f\"",
+        ),
+        ("\"\\u0022\\u005C\"", "\"\\\"\\\\\""),
+        // Other control characters use lower-case \u escapes.
+        ("\"\\u001F\\u0001\"", "\"\\u001f\\u0001\""),
+        ("\"\u{7f}\\u0085\u{9f}\"", "\"\\u007f\\u0085\\u009f\""),
+        // So do the soft hyphen and everything beyond Latin-1.
+        ("\"\u{ad}\\u00AD\"", "\"\\u00ad\\u00ad\""),
+        ("\"\\u00e9\u{a0}\u{ff}\"", "\"\u{e9}\u{a0}\u{ff}\""),
+        ("\"\u{200b}\\u20AC\"", "\"\\u200b\\u20ac\""),
+        ("\"\\uD83D\\uDE00\"", "\"\\ud83d\\ude00\""),
+    ] {
+        let output = canonicalize_str(input).unwrap();
+        assert_eq!(output, canonical, "{}", input);
+        assert_eq!(canonicalize_str(&output).unwrap(), output);
+    }
+}
+
+#[test]
+fn final_newline_option() {
+    let value: CargoValue = "{\"a\": [1]}".parse().unwrap();
+    let write = |indent: Option<u32>, final_newline: Option<bool>| {
+        let opts = WriteOpts {
+            indent: indent.map(IndentStyle::Spaces),
+            final_newline,
+            ..WriteOpts::default()
+        };
+        let mut out = String::new();
+        write_cargo_value(&mut out, &value, &opts).unwrap();
+        let mut streamed = Vec::new();
+        canonicalize_stream(value.to_string().as_bytes(), &mut streamed, &opts).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), out);
+        out
+    };
+    assert_eq!(write(None, None), "{\"a\":[1]}");
+    assert_eq!(write(None, Some(false)), "{\"a\":[1]}");
+    assert_eq!(write(None, Some(true)), "{\"a\":[1]}\n");
+    assert_eq!(write(Some(1), None), "{\n \"a\": [\n  1\n ]\n}\n");
+    assert_eq!(write(Some(1), Some(true)), "{\n \"a\": [\n  1\n ]\n}\n");
+    assert_eq!(write(Some(1), Some(false)), "{\n \"a\": [\n  1\n ]\n}");
+}
+
+#[test]
+fn crlf_line_endings() {
+    let value: CargoValue = "{\"a\": [1, 2], \"b\": {}}".parse().unwrap();
+    let opts = WriteOpts {
+        indent: Some(IndentStyle::Spaces(2)),
+        line_ending: LineEnding::Crlf,
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, pretty(&value, 2).replace('\n', "\r\n"));
+    assert_eq!(out.matches('\n').count(), out.matches("\r\n").count());
+    let mut streamed = Vec::new();
+    canonicalize_stream(value.to_string().as_bytes(), &mut streamed, &opts).unwrap();
+    assert_eq!(String::from_utf8(streamed).unwrap(), out);
+    assert_eq!(parse(out.as_bytes()).unwrap(), value);
+}
+
+#[test]
+fn ascii_only_option() {
+    let value: CargoValue =
+        "{\"caf\u{e9}\": [\"caf\u{e9}\", \"\u{1f600}\", \"\u{a0}\u{ff}\u{100}~\"]}"
+            .parse()
+            .unwrap();
+    let opts = WriteOpts {
+        ascii_only: true,
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(
+        out,
+        "{\"caf\\u00e9\":[\"caf\\u00e9\",\"\\ud83d\\ude00\",\"\\u00a0\\u00ff\\u0100~\"]}"
+    );
+    assert!(out.is_ascii());
+    assert_eq!(
+        value.to_string(),
+        "{\"caf\u{e9}\":[\"caf\u{e9}\",\"\\ud83d\\ude00\",\"\u{a0}\u{ff}\\u0100~\"]}"
+    );
+    let mut streamed = Vec::new();
+    canonicalize_stream(value.to_string().as_bytes(), &mut streamed, &opts).unwrap();
+    assert_eq!(String::from_utf8(streamed).unwrap(), out);
+    assert_eq!(parse(out.as_bytes()).unwrap(), value);
+}
+
+#[test]
+fn integers_as_floats_option() {
+    let opts = WriteOpts {
+        integers_as_floats: true,
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    let value: CargoValue = "[1,2,3]".parse().unwrap();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, "[1.0,2.0,3.0]");
+    assert_eq!(canonicalize_str("[1,2,3]").unwrap(), "[1,2,3]");
+
+    // Other numbers are written as usual, and the integers keep all their digits.
+    let value: CargoValue = "[-7, 0, 2.5, 9007199254740993]".parse().unwrap();
+    out.clear();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, "[-7.0,0.0,0.25e1,9007199254740993.0]");
+    let reread = parse(out.as_bytes()).unwrap();
+    let reread = reread.content().as_array().unwrap();
+    assert_eq!(reread[0].content().as_f64(), Some(-7.0));
+    assert_eq!(reread[3].content().as_f64(), Some(9007199254740992.0));
+    let mut streamed = Vec::new();
+    canonicalize_stream(
+        "[-7, 0, 2.5, 9007199254740993]".as_bytes(),
+        &mut streamed,
+        &opts,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(streamed).unwrap(), out);
+}
+
+#[test]
+fn preserve_whitespace_reproduces_input() {
+    let input = "\n  {\n  \"name\": \"rs-cargo\",\n  \"tags\" : [ \"json\",  \"parser\" ],\n\
+                 \t\"empty\":{ },\"none\" :[],\n  \"nested\": {\"a\":1.50,   \"b\" : [\r\n ]  ,\"c\":null},\n\
+                 \"n\": -0.0e+1 , \"deep\": [[ [1] ,2 ],{}]\n}\n\n";
+    let config = ParseConfig::new().preserve_whitespace(true);
+    let value = from_reader_with_config(input.as_bytes(), &config).unwrap();
+    let opts = WriteOpts {
+        preserve_whitespace: true,
+        verbatim_numbers: true,
+        ..WriteOpts::default()
+    };
+    let write = |value: &CargoValue, opts: &WriteOpts| {
+        let mut out = String::new();
+        write_cargo_value(&mut out, value, opts).unwrap();
+        out
+    };
+    assert_eq!(write(&value, &opts), input);
+    // Without the write option, or without the whitespace having been recorded,
+    // the output is canonical as usual.
+    let canonical = value.to_string();
+    assert_eq!(write(&value, &WriteOpts::default()), canonical);
+    let plain = parse(input.as_bytes()).unwrap();
+    assert!(plain.whitespace().is_none());
+    assert_eq!(
+        write(&plain, &opts),
+        canonical
+            .replace("0.15e1", "1.50")
+            .replace("-0.0", "-0.0e+1")
+    );
+    assert_eq!(value, plain);
+
+    let space = value.whitespace().unwrap();
+    assert_eq!(
+        (space.before.as_str(), space.after.as_str()),
+        ("\n  ", "\n\n")
+    );
+    let object = value.content().as_object().unwrap();
+    let tags = object.get("tags").unwrap().whitespace().unwrap();
+    assert_eq!(tags.before, "\n  ");
+    assert_eq!(tags.after_name, " ");
+    assert_eq!(tags.after_colon, " ");
+    assert_eq!(tags.after, "");
+    assert_eq!(tags.inside, " ");
+    assert_eq!(space.inside, "\n");
+    assert_eq!(
+        object.get("empty").unwrap().whitespace().unwrap().inside,
+        " "
+    );
+}
+
+#[test]
+fn preserve_whitespace_survives_edits() {
+    let input = "{\n  \"a\": 1,\n  \"b\": [ 2 ]\n}";
+    let config = ParseConfig::new().preserve_whitespace(true);
+    let mut value = from_reader_with_config(input.as_bytes(), &config).unwrap();
+    let object = value.as_object_mut().unwrap();
+    object
+        .get_mut("a")
+        .unwrap()
+        .set_content(CargoContent::Number(CargoNumber::from(10)));
+    object.insert("c".into(), CargoValue::bool(true));
+    let opts = WriteOpts {
+        preserve_whitespace: true,
+        indent: Some(IndentStyle::Spaces(2)),
+        ..WriteOpts::default()
+    };
+    let mut out = String::new();
+    write_cargo_value(&mut out, &value, &opts).unwrap();
+    assert_eq!(out, "{\n  \"a\": 10,\n  \"b\": [ 2 ],\n  \"c\": true\n}\n");
+}
+
+/*
+ * Accepts the first "limit" bytes written to it and fails every write after
+ * that, counting how many writes were attempted once it had failed.
+ */
+struct FailingWriter {
+    written: Vec<u8>,
+    limit: usize,
+    writes_after_failure: usize,
+}
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written.len() >= self.limit {
+            self.writes_after_failure += 1;
+            return Err(io::Error::other("disk full"));
+        }
+        let n = buf.len().min(self.limit - self.written.len());
+        self.written.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn buffer_size_option() {
+    let input = format!("[{}]", vec!["{\"a\": [1.5, \"x\"]}"; 1000].join(", "));
+    let value = parse(input.as_bytes()).unwrap();
+    for buffer_size in [None, Some(0), Some(1), Some(100)] {
+        let opts = WriteOpts {
+            buffer_size,
+            ..WriteOpts::default()
+        };
+        let mut out = Vec::new();
+        value.to_writer_with_opts(&mut out, &opts).unwrap();
+        assert_eq!(out, value.to_string().as_bytes());
+        let mut streamed = Vec::new();
+        canonicalize_stream(input.as_bytes(), &mut streamed, &opts).unwrap();
+        assert_eq!(streamed, out);
+    }
+}
+
+#[test]
+fn failed_writes_are_not_retried() {
+    let value = parse(format!("[{}]", vec!["123"; 10000].join(",")).as_bytes()).unwrap();
+    let mut w = FailingWriter {
+        written: Vec::new(),
+        limit: 1000,
+        writes_after_failure: 0,
+    };
+    let err = value.to_writer(&mut w).unwrap_err();
+    assert!(matches!(err, CargoError::Io(_)), "{:?}", err);
+    assert_eq!(w.written, value.to_string().as_bytes()[..1000]);
+    assert_eq!(w.writes_after_failure, 1);
+    // Invalid input still has what came before the error flushed out.
+    let mut out = Vec::new();
+    let opts = WriteOpts {
+        buffer_size: Some(1 << 20),
+        ..WriteOpts::default()
+    };
+    assert!(canonicalize_stream(&b"[1, 2, x]"[..], &mut out, &opts).is_err());
+    assert_eq!(out, b"[1,2");
+}
+
+#[test]
+fn default_string_and_number() {
+    let string = CargoString::default();
+    assert_eq!(string.as_str(), "");
+    assert_eq!((string.len(), string.capacity()), (0, 0));
+    assert!(string.is_empty());
+    assert_eq!(CargoContent::String(string).to_string(), "\"\"");
+    let number = CargoNumber::default();
+    assert_eq!(number.as_i64(), Some(0));
+    assert_eq!(number.as_f64(), Some(0.0));
+    assert_eq!(CargoContent::Number(number).to_string(), "0");
+}
+
+#[test]
+fn canonical_exponents_have_no_sign_or_leading_zeros() {
+    for (input, expected) in [
+        ("1e5", "0.1e6"),
+        ("1E5", "0.1e6"),
+        ("1e+05", "0.1e6"),
+        ("1e0005", "0.1e6"),
+        ("100000.0", "0.1e6"),
+        ("1e-05", "0.1e-4"),
+        ("-25E+001", "-0.25e3"),
+        ("1e+0", "0.1e1"),
+        ("0.1e+1", "0.1e1"),
+        ("0.5e-0", "0.5"),
+    ] {
+        let value = parse(input.as_bytes()).unwrap();
+        assert_eq!(value.to_string(), expected, "{}", input);
+        let mut streamed = Vec::new();
+        canonicalize_stream(input.as_bytes(), &mut streamed, &WriteOpts::default()).unwrap();
+        assert_eq!(streamed, expected.as_bytes(), "{}", input);
+        assert_eq!(canonicalize_str(expected).unwrap(), expected);
+    }
+}
+
+#[test]
+fn canonical_form_of_zeros() {
+    for (input, expected) in [
+        ("0", "0"),
+        ("-0", "0"),
+        ("0.0", "0.0"),
+        ("-0.0", "-0.0"),
+        ("0e5", "0.0"),
+        ("-0e-5", "-0.0"),
+    ] {
+        let value = parse(input.as_bytes()).unwrap();
+        assert_eq!(value.to_string(), expected, "{}", input);
+        let mut streamed = Vec::new();
+        canonicalize_stream(input.as_bytes(), &mut streamed, &WriteOpts::default()).unwrap();
+        assert_eq!(streamed, expected.as_bytes(), "{}", input);
+        assert_eq!(canonicalize_str(expected).unwrap(), expected);
+    }
+    let zero = parse(&b"-0"[..]).unwrap();
+    assert_eq!(zero.content().as_i64(), Some(0));
+    assert!(zero.content().as_f64().unwrap().is_sign_positive());
+    let negative = parse(&b"-0.0"[..]).unwrap();
+    assert_eq!(negative.content().as_i64(), None);
+    assert!(negative.content().as_f64().unwrap().is_sign_negative());
+    // The two are still equal, as IEEE 754 comparison has it.
+    assert_eq!(parse(&b"-0.0"[..]).unwrap(), parse(&b"0"[..]).unwrap());
+}
+
+#[test]
+fn layout_measures_pretty_output() {
+    let value =
+        parse(&b"{\"name\": \"rs-cargo\", \"tags\": [\"json\", 1.5], \"empty\": {}}"[..]).unwrap();
+    // {
+    //     "name": "rs-cargo",
+    //     "tags": [
+    //         "json",
+    //         0.15e1
+    //     ],
+    //     "empty": {
+    //     }
+    // }
+    let spaces = WriteOpts {
+        indent: Some(IndentStyle::Spaces(4)),
+        ..WriteOpts::default()
+    };
+    let layout = CargoLayout::of(&value, &spaces, 8);
+    assert_eq!(
+        layout,
+        CargoLayout {
+            lines: 9,
+            max_width: 23
+        }
+    );
+    assert_eq!(layout.to_string(), "lines: 9\nmax width: 23");
+    let text = value.to_string_pretty(4);
+    assert_eq!(text.lines().count(), layout.lines);
+    assert_eq!(text.lines().map(str::len).max(), Some(layout.max_width));
+    let compact = CargoLayout::of(&value, &WriteOpts::default(), 8);
+    assert_eq!(compact.lines, 1);
+    assert_eq!(compact.max_width, value.to_string().len());
+    let tabs = WriteOpts {
+        indent: Some(IndentStyle::Tabs),
+        line_ending: LineEnding::Crlf,
+        ..WriteOpts::default()
+    };
+    assert_eq!(CargoLayout::of(&value, &tabs, 8).max_width, 8 + 19);
+    assert_eq!(CargoLayout::of(&value, &tabs, 2).max_width, 2 + 19);
+    assert_eq!(
+        CargoLayout::of(&parse(&b"\"\""[..]).unwrap(), &tabs, 8).lines,
+        1
+    );
+}